@@ -2,22 +2,25 @@ use anyhow::Result;
 use cargo_metadata::MetadataCommand;
 use clap::Parser;
 
-use cargo_goosectl::cli::CargoGooseCli;
+use cargo_goosectl::{cli::CargoGooseCli, metadata::Metadata};
 
 fn main() -> Result<()> {
-    // TODO: complete when we're ready to support configs
-    // let config: Config = figment::Figment::new()
-    //     .merge(figment::providers::Toml::file("goosectl.toml"))
-    //     .extract()?;
-
     // get cargo metadata
-    let metadata = MetadataCommand::new().exec()?.into();
+    let metadata: Metadata = MetadataCommand::new().exec()?.into();
 
     // parse args
     let args = CargoGooseCli::parse();
 
     match args {
-        CargoGooseCli::Goosectl(args) => args.execute(&metadata)?,
+        CargoGooseCli::Goosectl(args) => {
+            if let Err(err) = args.execute(&metadata, &std::env::current_dir()?) {
+                // usage errors print the way clap prints its own
+                if let Some(err) = err.downcast_ref::<clap::Error>() {
+                    err.exit();
+                }
+                return Err(err);
+            }
+        }
     }
 
     Ok(())
@@ -1,17 +1,17 @@
 use std::{fs::File, io::Write, path::PathBuf};
 
 use anyhow::Result;
-use schemars::{JsonSchema, schema_for};
+use schemars::Schema;
 
 const SCHEMA_PATH: &str = "schemas";
 
 fn main() -> Result<()> {
-    write_to_path::<cargo_goosectl::config::Config>("goosectl")?;
+    write_to_path(cargo_goosectl::config::schema(), "goosectl")?;
+    write_to_path(cargo_goosectl::cli::bump_report_schema(), "bump-report")?;
     Ok(())
 }
 
-fn write_to_path<T: JsonSchema>(name: &str) -> Result<()> {
-    let schema = schema_for!(T);
+fn write_to_path(schema: Schema, name: &str) -> Result<()> {
     let schema_bytes = serde_json::to_vec_pretty(&schema)?;
 
     let path = PathBuf::from(SCHEMA_PATH).join(format!("{name}.schema.json"));
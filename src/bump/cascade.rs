@@ -0,0 +1,319 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use anyhow::{Context, Result, bail};
+use cargo_metadata::{DependencyKind, Package, semver::Version};
+
+use crate::{
+    config::WorkspaceConfig,
+    metadata::Metadata,
+    version::{
+        semantic_version::{ReleaseLevel, SemanticVersion},
+        transition::TransitionInput,
+    },
+};
+
+/// A dependent bumped because some of its dependencies were.
+#[derive(Debug)]
+pub struct Cascaded<'a> {
+    pub package: &'a Package,
+    pub from: SemanticVersion,
+    pub to: SemanticVersion,
+    pub transition: TransitionInput,
+    /// The bumped dependencies that caused it.
+    pub causes: Vec<String>,
+}
+
+/// Whether going from `old` to `new` breaks Cargo's compatibility rules, i.e.
+/// the left-most non-zero component changed.
+pub fn is_breaking(old: &Version, new: &Version) -> bool {
+    match (old.major, old.minor) {
+        (0, 0) => (new.major, new.minor, new.patch) != (0, 0, old.patch),
+        (0, _) => (new.major, new.minor) != (0, old.minor),
+        _ => new.major != old.major,
+    }
+}
+
+/// The most significant component that changed between `old` and `new`.
+pub fn change_level(old: &Version, new: &Version) -> ReleaseLevel {
+    if old.major != new.major {
+        ReleaseLevel::Major
+    } else if old.minor != new.minor {
+        ReleaseLevel::Minor
+    } else {
+        ReleaseLevel::Patch
+    }
+}
+
+/// Members of the workspace that `package` depends on by path.
+/// Dev-dependencies are left out, as they do not affect dependents.
+fn member_dependencies<'a>(
+    package: &'a Package,
+    member_dirs: &'a HashMap<String, &'a Path>,
+) -> impl Iterator<Item = &'a str> {
+    package.dependencies.iter().filter_map(move |dep| {
+        let dir = member_dirs.get(dep.name.as_str())?;
+        let on_member = dep.path.as_ref()?.as_std_path() == *dir;
+
+        (on_member && dep.kind != DependencyKind::Development).then_some(dep.name.as_str())
+    })
+}
+
+fn member_dirs<'a>(members: &[&'a Package]) -> HashMap<String, &'a Path> {
+    members
+        .iter()
+        .filter_map(|package| {
+            let dir = package.manifest_path.parent()?.as_std_path();
+            Some((package.name.to_string(), dir))
+        })
+        .collect()
+}
+
+/// The packages in `bumped` that `package` depends on by path.
+pub fn bumped_dependencies<V>(
+    metadata: &Metadata,
+    package: &Package,
+    bumped: &HashMap<String, V>,
+) -> Vec<String> {
+    let dirs = member_dirs(&metadata.workspace_members());
+
+    let mut dependencies: Vec<String> = member_dependencies(package, &dirs)
+        .filter(|dep| bumped.contains_key(*dep))
+        .map(str::to_string)
+        .collect();
+    dependencies.dedup();
+    dependencies
+}
+
+/// Workspace members ordered so that each comes after the members it depends on.
+pub fn dependency_order(metadata: &Metadata) -> Result<Vec<&Package>> {
+    let members = metadata.workspace_members();
+    let dirs = member_dirs(&members);
+
+    let mut order: Vec<&Package> = Vec::new();
+    let mut placed = HashSet::new();
+
+    while order.len() < members.len() {
+        let ready: Vec<&Package> = members
+            .iter()
+            .filter(|package| !placed.contains(package.name.as_str()))
+            .filter(|package| member_dependencies(package, &dirs).all(|dep| placed.contains(dep)))
+            .copied()
+            .collect();
+
+        if ready.is_empty() {
+            let remaining: Vec<&str> = members
+                .iter()
+                .map(|package| package.name.as_str())
+                .filter(|name| !placed.contains(name))
+                .collect();
+            bail!(
+                "workspace members depend on each other in a cycle: {}",
+                remaining.join(", ")
+            );
+        }
+
+        for package in ready {
+            placed.insert(package.name.as_str());
+            order.push(package);
+        }
+    }
+
+    Ok(order)
+}
+
+/// Bump every member that depends on a bumped package, walking the dependency
+/// graph so that cascaded bumps cascade further.
+///
+/// `bumped` maps already bumped packages to their old and new versions and
+/// receives the cascaded ones. Members in `exclude` are never cascaded into.
+/// A dependent on a release gets a release bump at the level its
+/// [`CascadeConfig`](crate::config::CascadeConfig) gives; one on a prerelease
+/// gets its next prerelease.
+pub fn cascade<'a>(
+    metadata: &'a Metadata,
+    config: &WorkspaceConfig,
+    bumped: &mut HashMap<String, (SemanticVersion, SemanticVersion)>,
+    exclude: &HashSet<String>,
+) -> Result<Vec<Cascaded<'a>>> {
+    let members = metadata.workspace_members();
+    let dirs = member_dirs(&members);
+
+    let mut cascaded = Vec::new();
+
+    for package in dependency_order(metadata)? {
+        if bumped.contains_key(package.name.as_str()) || exclude.contains(package.name.as_str()) {
+            continue;
+        }
+
+        let rules = config.package(&package.name).project().cascade();
+        let mut level = None;
+        let mut causes = Vec::new();
+
+        for dep in member_dependencies(package, &dirs) {
+            let Some((old, new)) = bumped.get(dep) else {
+                continue;
+            };
+
+            let (old, new) = (old.version(), new.version());
+            let dep_level = rules.level(change_level(old, new), is_breaking(old, new));
+
+            level = level.max(Some(dep_level));
+            if !causes.iter().any(|cause| cause == dep) {
+                causes.push(dep.to_string());
+            }
+        }
+
+        let Some(level) = level else {
+            continue;
+        };
+
+        let from = config.package_version(package)?;
+        let transition = match from.is_prerelease() {
+            true => TransitionInput::IncrementPrerelease { metadata: None },
+            false => TransitionInput::BumpRelease {
+                level,
+                metadata: None,
+            },
+        };
+        let to = from
+            .apply(transition.clone())
+            .with_context(|| format!("cannot cascade into `{}`", package.name))?;
+
+        bumped.insert(package.name.to_string(), (from.clone(), to.clone()));
+        cascaded.push(Cascaded {
+            package,
+            from,
+            to,
+            transition,
+            causes,
+        });
+    }
+
+    Ok(cascaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Workspace;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    fn sv(s: &str) -> SemanticVersion {
+        SemanticVersion::parse_with(v(s), crate::version::scheme::STRICT.clone()).unwrap()
+    }
+
+    #[test]
+    fn breaking_follows_cargo_rules() {
+        assert!(is_breaking(&v("1.2.3"), &v("2.0.0")));
+        assert!(!is_breaking(&v("1.2.3"), &v("1.3.0")));
+        assert!(is_breaking(&v("0.2.3"), &v("0.3.0")));
+        assert!(!is_breaking(&v("0.2.3"), &v("0.2.4")));
+        assert!(is_breaking(&v("0.0.3"), &v("0.0.4")));
+    }
+
+    /// `core` <- `mid` <- `app`, plus `tool` on its own; `app` also has a
+    /// dev-dependency on `tool`.
+    fn workspace() -> Workspace {
+        let workspace =
+            Workspace::new("[workspace]\nmembers = [\"core\", \"mid\", \"app\", \"tool\"]\n");
+        workspace
+            .package(
+                "app",
+                r#"[package]
+name = "app"
+version = "3.0.0"
+edition = "2021"
+
+[dependencies]
+mid = { path = "../mid", version = "2.0.0" }
+
+[dev-dependencies]
+tool = { path = "../tool", version = "0.1.0" }
+"#,
+            )
+            .package(
+                "mid",
+                r#"[package]
+name = "mid"
+version = "2.0.0"
+edition = "2021"
+
+[dependencies]
+core = { path = "../core", version = "1.0.0" }
+"#,
+            )
+            .package(
+                "core",
+                "[package]\nname = \"core\"\nversion = \"1.0.0\"\nedition = \"2021\"\n",
+            )
+            .package(
+                "tool",
+                "[package]\nname = \"tool\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            );
+        workspace
+    }
+
+    fn names(packages: &[&Package]) -> Vec<String> {
+        packages.iter().map(|p| p.name.to_string()).collect()
+    }
+
+    #[test]
+    fn orders_dependencies_first() {
+        let workspace = workspace();
+        let metadata = workspace.metadata();
+
+        let order = names(&dependency_order(&metadata).unwrap());
+        let position = |name: &str| order.iter().position(|n| n == name).unwrap();
+
+        assert!(position("core") < position("mid"));
+        assert!(position("mid") < position("app"));
+    }
+
+    fn cascade_from(
+        workspace: &Workspace,
+        package: &str,
+        from: &str,
+        to: &str,
+    ) -> HashMap<String, String> {
+        let metadata = workspace.metadata();
+        let config = WorkspaceConfig::load(None, workspace.root(), &metadata).unwrap();
+
+        let mut bumped = HashMap::from([(package.to_string(), (sv(from), sv(to)))]);
+        cascade(&metadata, &config, &mut bumped, &HashSet::new()).unwrap();
+
+        bumped
+            .into_iter()
+            .map(|(name, (_, to))| (name, to.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn compatible_change_cascades_patch() {
+        let bumped = cascade_from(&workspace(), "core", "1.0.0", "1.1.0");
+
+        assert_eq!(bumped["mid"], "2.0.1");
+        assert_eq!(bumped["app"], "3.0.1");
+        assert!(!bumped.contains_key("tool"));
+    }
+
+    #[test]
+    fn breaking_change_cascades_same_level() {
+        let bumped = cascade_from(&workspace(), "core", "1.0.0", "2.0.0");
+
+        assert_eq!(bumped["mid"], "3.0.0");
+        assert_eq!(bumped["app"], "4.0.0");
+    }
+
+    #[test]
+    fn dev_dependencies_do_not_cascade() {
+        let bumped = cascade_from(&workspace(), "tool", "0.1.0", "0.2.0");
+
+        assert_eq!(bumped.len(), 1);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod cascade;
+pub mod plan;
@@ -0,0 +1,1222 @@
+use std::{
+    collections::{BTreeMap, HashMap, btree_map::Entry},
+    fmt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use cargo_metadata::{DependencyKind, Package, semver::VersionReq};
+use similar::TextDiff;
+use toml_edit::{DocumentMut, TableLike};
+
+use super::cascade;
+use crate::{
+    config::{BrokenDependents, Config, Language, WorkspaceConfig},
+    manifest,
+    metadata::Metadata,
+    version::{requirement, semantic_version::SemanticVersion, transition::TransitionInput},
+};
+
+/// When dependents' requirements on bumped packages are rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Propagation {
+    /// Rewrite every requirement on a bumped package.
+    Always,
+    /// Only rewrite requirements the new version no longer satisfies.
+    WhenNeeded,
+    /// Leave dependents untouched.
+    Never,
+}
+
+/// Settings for [`BumpPlan::new`].
+#[derive(Debug, Clone, Default)]
+pub struct BumpOptions {
+    /// When to rewrite dependents' requirements. When unset, requirements are
+    /// rewritten only if more than one package is bumped.
+    pub propagation: Option<Propagation>,
+    /// Also bump every member that depends on a bumped package.
+    pub cascade: bool,
+    /// How `Cargo.lock` follows the new versions.
+    pub lockfile: LockfileUpdate,
+}
+
+/// How `Cargo.lock` is brought up to date with bumped members.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum LockfileUpdate {
+    /// Edit the entries of bumped members in place, without resolving.
+    #[default]
+    Edit,
+    /// Run `cargo update --workspace --offline` once the manifests are written.
+    Cargo,
+}
+
+/// Why a package gets a new version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BumpReason {
+    /// It was selected.
+    Selected,
+    /// It depends on these bumped packages.
+    Cascaded(Vec<String>),
+}
+
+/// A package moving to a new version.
+#[derive(Debug, Clone)]
+pub struct PackageBump {
+    pub name: String,
+    pub from: SemanticVersion,
+    pub to: SemanticVersion,
+    pub transition: TransitionInput,
+    /// Whether the version lives in `[workspace.package]`.
+    pub inherited: bool,
+    pub reason: BumpReason,
+}
+
+/// What a [`ManifestEdit`] changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditTarget {
+    /// The `package.version` of a member.
+    PackageVersion { package: String },
+    /// `workspace.package.version`, inherited by these members.
+    WorkspaceVersion { members: Vec<String> },
+    /// The version recorded for a member in `Cargo.lock`.
+    Lockfile { package: String },
+    /// A requirement on the bumped `dependency`, in the manifest of
+    /// `dependent`, or in `[workspace.dependencies]` when there is none.
+    Dependency {
+        dependent: Option<String>,
+        dependency: String,
+    },
+}
+
+/// A single value changed in a manifest.
+#[derive(Debug, Clone)]
+pub struct ManifestEdit {
+    pub manifest: PathBuf,
+    /// Keys leading to the value, e.g. `["dependencies", "core", "version"]`.
+    pub path: Vec<String>,
+    /// The previous value, if there was one.
+    pub from: Option<String>,
+    pub to: String,
+    pub target: EditTarget,
+}
+
+/// Something about a dependent's requirement on a bumped package that is
+/// worth reporting, but is not an edit.
+///
+/// `dependency` is the key the dependency is declared under, except for
+/// [`Note::Broken`] which names the package.
+#[derive(Debug, Clone)]
+pub enum Note {
+    /// The requirement comes from `[workspace.dependencies]`, which was
+    /// updated to `requirement`.
+    Inherited {
+        dependent: String,
+        dependency: String,
+        requirement: String,
+    },
+    /// The requirement still holds and was left alone.
+    Satisfied {
+        dependent: Option<String>,
+        dependency: String,
+        requirement: String,
+        version: SemanticVersion,
+    },
+    /// The requirement cannot be moved without changing its meaning.
+    Kept {
+        dependent: Option<String>,
+        dependency: String,
+        requirement: String,
+        version: SemanticVersion,
+    },
+    /// The requirement no longer holds and was not propagated.
+    Broken {
+        dependent: String,
+        dependency: String,
+        requirement: String,
+        kind: DependencyKind,
+        version: SemanticVersion,
+    },
+    /// A dependent of the bumped `causes` that was not cascaded into, as it
+    /// inherits its version from `[workspace.package]`.
+    NotCascaded {
+        dependent: String,
+        causes: Vec<String>,
+    },
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = |dependent: &Option<String>| match dependent {
+            Some(dependent) => format!("package {dependent}"),
+            None => "[workspace.dependencies]".to_string(),
+        };
+
+        match self {
+            Note::Inherited {
+                dependent,
+                dependency,
+                requirement,
+            } => write!(
+                f,
+                "dependency {dependency} in package {dependent} is now {requirement} \
+                 (via [workspace.dependencies])"
+            ),
+            Note::Satisfied {
+                dependent,
+                dependency,
+                requirement,
+                version,
+            } => write!(
+                f,
+                "dependency {dependency} in {}: {version} still satisfies {requirement}",
+                location(dependent)
+            ),
+            Note::Kept {
+                dependent,
+                dependency,
+                requirement,
+                version,
+            } => write!(
+                f,
+                "dependency {dependency} in {} kept at {requirement}: cannot retarget it to {version}",
+                location(dependent)
+            ),
+            Note::Broken {
+                dependent,
+                dependency,
+                requirement,
+                kind,
+                version,
+            } => {
+                let kind = match kind {
+                    DependencyKind::Development => " (dev)",
+                    DependencyKind::Build => " (build)",
+                    _ => "",
+                };
+                write!(
+                    f,
+                    "{dependent} requires {dependency} {requirement}{kind}, which is now {version}"
+                )
+            }
+            Note::NotCascaded { dependent, causes } => write!(
+                f,
+                "{dependent} depends on {} but was not bumped: it inherits its version \
+                 from [workspace.package]",
+                causes.join(", ")
+            ),
+        }
+    }
+}
+
+/// A manifest as read from disk and as it will be written.
+struct Staged {
+    original: String,
+    doc: DocumentMut,
+}
+
+/// Manifests edited in memory, by path, then written back together.
+#[derive(Default)]
+pub struct StagedManifests(BTreeMap<PathBuf, Staged>);
+
+impl StagedManifests {
+    /// The parsed manifest at `path`, read on first use.
+    pub fn stage(&mut self, path: &Path) -> Result<&mut DocumentMut> {
+        let staged = match self.0.entry(path.to_path_buf()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let (original, doc) = manifest::read_raw(path)?;
+                entry.insert(Staged { original, doc })
+            }
+        };
+
+        Ok(&mut staged.doc)
+    }
+
+    /// Each manifest whose contents change, with what will be written.
+    fn changed(&self) -> impl Iterator<Item = (&PathBuf, String, &Staged)> {
+        self.0
+            .iter()
+            .map(|(path, staged)| {
+                (
+                    path,
+                    manifest::render(&staged.doc, &staged.original),
+                    staged,
+                )
+            })
+            .filter(|(_, contents, staged)| *contents != staged.original)
+    }
+
+    /// Write every changed manifest, then run `finish` on the paths written.
+    ///
+    /// Either all of them are written or none are: if a write or `finish`
+    /// fails, those already written are restored to their original contents.
+    pub fn write(
+        &self,
+        root: &Path,
+        finish: impl FnOnce(&[&PathBuf]) -> Result<()>,
+    ) -> Result<Vec<&PathBuf>> {
+        let changed: Vec<_> = self.changed().collect();
+
+        for (path, _, _) in &changed {
+            manifest::ensure_inside(root, path)?;
+        }
+
+        let mut written = Vec::new();
+        for (path, contents, staged) in &changed {
+            if let Err(err) = manifest::replace(path, contents) {
+                return Err(rollback(&written, err));
+            }
+            written.push((*path, *staged));
+        }
+
+        let paths: Vec<_> = written.iter().map(|(path, _)| *path).collect();
+        if let Err(err) = finish(&paths) {
+            return Err(rollback(&written, err));
+        }
+
+        Ok(paths)
+    }
+}
+
+/// Everything a bump will do, worked out without touching the workspace.
+///
+/// Edits are made to in-memory copies of the manifests, which
+/// [`apply`](Self::apply) writes back.
+pub struct BumpPlan {
+    root: PathBuf,
+    lockfile: LockfileUpdate,
+    packages: Vec<PackageBump>,
+    edits: Vec<ManifestEdit>,
+    notes: Vec<Note>,
+    manifests: StagedManifests,
+}
+
+impl BumpPlan {
+    /// Plan applying each selected package's transition, then propagating
+    /// the new versions to the rest of the workspace as `options` says.
+    pub fn new(
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        selection: &[(&Package, TransitionInput)],
+        options: &BumpOptions,
+    ) -> Result<Self> {
+        let mut plan = Self {
+            root: metadata.workspace_root().to_path_buf(),
+            lockfile: options.lockfile,
+            packages: Vec::new(),
+            edits: Vec::new(),
+            notes: Vec::new(),
+            manifests: StagedManifests::default(),
+        };
+
+        // Members that take their version from `[workspace.package]` share it,
+        // so it is bumped once in the root manifest
+        let mut inheriting = Vec::new();
+        for member in metadata.workspace_members() {
+            let doc = plan.manifests.stage(member.manifest_path.as_std_path())?;
+            if manifest::inherits_version(doc) {
+                inheriting.push(member);
+            }
+        }
+
+        let (shared, own): (Vec<_>, Vec<_>) = selection
+            .iter()
+            .partition(|(package, _)| inheriting.iter().any(|member| member.id == package.id));
+
+        if let Some((first, transition)) = shared.first() {
+            let missing: Vec<_> = inheriting
+                .iter()
+                .filter(|member| !shared.iter().any(|(package, _)| package.id == member.id))
+                .map(|member| format!("`{}`", member.name))
+                .collect();
+
+            let names = || {
+                shared
+                    .iter()
+                    .map(|(package, _)| format!("`{}`", package.name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+
+            if !missing.is_empty() {
+                bail!(
+                    "cannot bump {} without {}: they all inherit their version from \
+                     `[workspace.package]`; select them together or use --workspace",
+                    names(),
+                    missing.join(", ")
+                );
+            }
+
+            if shared.iter().any(|(_, other)| other != transition) {
+                bail!(
+                    "cannot bump {} differently: they all inherit their version from \
+                     `[workspace.package]`",
+                    names()
+                );
+            }
+
+            let from = config.package_version(first)?;
+            let to = from.apply(transition.clone())?;
+
+            for (package, _) in &shared {
+                plan.packages.push(PackageBump {
+                    name: package.name.to_string(),
+                    from: from.clone(),
+                    to: to.clone(),
+                    transition: transition.clone(),
+                    inherited: true,
+                    reason: BumpReason::Selected,
+                });
+            }
+        }
+
+        for (package, transition) in own {
+            let from = config.package_version(package)?;
+            let to = from.apply(transition.clone())?;
+
+            plan.packages.push(PackageBump {
+                name: package.name.to_string(),
+                from,
+                to,
+                transition: transition.clone(),
+                inherited: false,
+                reason: BumpReason::Selected,
+            });
+        }
+
+        // Dependents of bumped packages get their own release
+        if options.cascade {
+            let mut versions = plan
+                .packages
+                .iter()
+                .map(|bump| (bump.name.clone(), (bump.from.clone(), bump.to.clone())))
+                .collect();
+
+            // the shared `[workspace.package]` version only moves when selected
+            let exclude = inheriting
+                .iter()
+                .map(|member| member.name.to_string())
+                .collect();
+
+            for cascaded in cascade::cascade(metadata, config, &mut versions, &exclude)? {
+                plan.packages.push(PackageBump {
+                    name: cascaded.package.name.to_string(),
+                    from: cascaded.from,
+                    to: cascaded.to,
+                    transition: cascaded.transition,
+                    inherited: false,
+                    reason: BumpReason::Cascaded(cascaded.causes),
+                });
+            }
+
+            for member in &inheriting {
+                if versions.contains_key(member.name.as_str()) {
+                    continue;
+                }
+
+                let causes = cascade::bumped_dependencies(metadata, member, &versions);
+                if !causes.is_empty() {
+                    plan.notes.push(Note::NotCascaded {
+                        dependent: member.name.to_string(),
+                        causes,
+                    });
+                }
+            }
+        }
+
+        plan.set_versions(metadata)?;
+
+        let propagation = options.propagation.unwrap_or(if plan.packages.len() > 1 {
+            Propagation::Always
+        } else {
+            Propagation::Never
+        });
+
+        match propagation {
+            Propagation::Never => plan.check_dependents(metadata, config)?,
+            _ => plan.propagate(metadata, config, propagation)?,
+        }
+
+        if options.lockfile == LockfileUpdate::Edit {
+            plan.update_lockfile(metadata)?;
+        }
+
+        Ok(plan)
+    }
+
+    pub fn packages(&self) -> &[PackageBump] {
+        &self.packages
+    }
+
+    pub fn edits(&self) -> &[ManifestEdit] {
+        &self.edits
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// A unified diff of every manifest the plan changes, from its contents
+    /// on disk to what [`apply`](Self::apply) would write.
+    pub fn diff(&self) -> String {
+        let mut out = String::new();
+
+        for (path, contents, staged) in self.manifests.changed() {
+            let name = path.strip_prefix(&self.root).unwrap_or(path).display();
+            let diff = TextDiff::from_lines(&staged.original, &contents);
+
+            out.push_str(
+                &diff
+                    .unified_diff()
+                    .header(&format!("a/{name}"), &format!("b/{name}"))
+                    .to_string(),
+            );
+        }
+
+        out
+    }
+
+    /// Write every manifest the plan changes.
+    ///
+    /// Either all of them are written or none are: if one fails, those
+    /// already written are restored to their original contents.
+    pub fn apply(&self) -> Result<()> {
+        self.manifests.write(&self.root, |written| {
+            if self.lockfile == LockfileUpdate::Cargo && !written.is_empty() {
+                cargo_update(&self.root)?;
+            }
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// New versions of bumped packages, by name.
+    fn updated(&self) -> HashMap<String, SemanticVersion> {
+        self.packages
+            .iter()
+            .map(|bump| (bump.name.clone(), bump.to.clone()))
+            .collect()
+    }
+
+    /// Record the new versions in `package.version`, or once in
+    /// `[workspace.package]` for members that inherit it.
+    fn set_versions(&mut self, metadata: &Metadata) -> Result<()> {
+        let mut edits = Vec::new();
+
+        let shared: Vec<&PackageBump> = self.packages.iter().filter(|b| b.inherited).collect();
+        if let Some(first) = shared.first() {
+            let path = metadata.workspace_root().join("Cargo.toml");
+            let doc = self.manifests.stage(&path)?;
+            let version = manifest::workspace_version_mut(doc).ok_or_else(|| {
+                anyhow!("`{}` has no `[workspace.package]` version", path.display())
+            })?;
+
+            let from = version.as_str().map(str::to_string);
+            manifest::set_str(version, first.to.to_string());
+
+            edits.push(ManifestEdit {
+                manifest: path,
+                path: vec!["workspace".into(), "package".into(), "version".into()],
+                from,
+                to: first.to.to_string(),
+                target: EditTarget::WorkspaceVersion {
+                    members: shared.iter().map(|bump| bump.name.clone()).collect(),
+                },
+            });
+        }
+
+        for bump in self.packages.iter().filter(|b| !b.inherited) {
+            let package = metadata
+                .workspace_members()
+                .into_iter()
+                .find(|package| package.name == bump.name)
+                .ok_or_else(|| anyhow!("package `{}` is not a workspace member", bump.name))?;
+
+            let path = package.manifest_path.as_std_path();
+            let doc = self.manifests.stage(path)?;
+            let version = &mut doc["package"]["version"];
+
+            let from = version.as_str().map(str::to_string);
+            manifest::set_str(version, bump.to.to_string());
+
+            edits.push(ManifestEdit {
+                manifest: path.to_path_buf(),
+                path: vec!["package".into(), "version".into()],
+                from,
+                to: bump.to.to_string(),
+                target: EditTarget::PackageVersion {
+                    package: bump.name.clone(),
+                },
+            });
+        }
+
+        self.edits.extend(edits);
+        Ok(())
+    }
+
+    /// Move the `Cargo.lock` entries of bumped members to their new versions,
+    /// along with references to them that spell out the version.
+    fn update_lockfile(&mut self, metadata: &Metadata) -> Result<()> {
+        let path = metadata.workspace_root().join("Cargo.lock");
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let doc = self.manifests.stage(&path)?;
+        for bump in &self.packages {
+            let from = bump.from.to_string();
+            let to = bump.to.to_string();
+            let Some(index) = manifest::set_locked_version(doc, &bump.name, &from, &to) else {
+                continue;
+            };
+
+            self.edits.push(ManifestEdit {
+                manifest: path.clone(),
+                path: vec!["package".into(), index.to_string(), "version".into()],
+                from: Some(from),
+                to,
+                target: EditTarget::Lockfile {
+                    package: bump.name.clone(),
+                },
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Without propagation, dependents may be left requiring the old versions.
+    fn check_dependents(&mut self, metadata: &Metadata, config: &WorkspaceConfig) -> Result<()> {
+        let broken = broken_dependents(metadata, &member_dirs(metadata), &self.updated());
+
+        if broken.is_empty() {
+            return Ok(());
+        }
+
+        if config.workspace().project().broken_dependents() == BrokenDependents::Fail {
+            let list = broken
+                .iter()
+                .map(|note| format!("  {note}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            bail!(
+                "these requirements would no longer be satisfied \
+                 (use --propagate to update them):\n{list}"
+            );
+        }
+
+        self.notes.extend(broken);
+        Ok(())
+    }
+
+    /// Rewrite requirements on bumped members, in `[workspace.dependencies]`
+    /// and in every member manifest.
+    fn propagate(
+        &mut self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        propagation: Propagation,
+    ) -> Result<()> {
+        let updated = self.updated();
+        let member_dirs = member_dirs(metadata);
+
+        let mut edits = Vec::new();
+        let mut notes = Vec::new();
+
+        // Requirements follow the style configured for the manifest being edited
+        let requirement_style =
+            |config: &Config| config.language_config(&Language::Rust).requirement_style();
+
+        // The bumped package and its new version for a dependency entry, if it
+        // is a path dependency on a bumped member (resolving `package = "..."`
+        // renames)
+        let bumped = |key: &str, dependency: &dyn TableLike, manifest_dir: &Path| {
+            let name = manifest::dependency_package(key, dependency);
+            let dir = member_dirs.get(&name)?;
+
+            manifest::path_points_at(manifest_dir, dependency, dir)
+                .then(|| updated.get(&name).map(|version| (name, version.clone())))
+                .flatten()
+        };
+
+        // Work out the requirement to write, or note why there is none
+        let rewrite = |current: Option<&str>,
+                       version: &SemanticVersion,
+                       config: &Config,
+                       dependent: Option<&str>,
+                       dependency: &str,
+                       notes: &mut Vec<Note>|
+         -> Option<String> {
+            // With `when-needed`, requirements the new version still satisfies are kept
+            let satisfied =
+                current.is_none_or(|current| requirement::satisfied(current, version.version()));
+
+            if propagation == Propagation::WhenNeeded && satisfied {
+                notes.push(Note::Satisfied {
+                    dependent: dependent.map(str::to_string),
+                    dependency: dependency.to_string(),
+                    requirement: current.unwrap_or("*").to_string(),
+                    version: version.clone(),
+                });
+                return None;
+            }
+
+            let requirement = requirement_style(config).requirement(current, version.version());
+
+            // a requirement that already reads the same (`^1` for 1.3.0) needs no edit
+            if requirement.is_some() && requirement.as_deref() == current {
+                notes.push(Note::Satisfied {
+                    dependent: dependent.map(str::to_string),
+                    dependency: dependency.to_string(),
+                    requirement: current.unwrap_or("*").to_string(),
+                    version: version.clone(),
+                });
+                return None;
+            }
+
+            if let (None, Some(current)) = (&requirement, current) {
+                notes.push(Note::Kept {
+                    dependent: dependent.map(str::to_string),
+                    dependency: dependency.to_string(),
+                    requirement: current.to_string(),
+                    version: version.clone(),
+                });
+            }
+
+            requirement
+        };
+
+        // Internal crates declared once in `[workspace.dependencies]`
+        let root_manifest = metadata.workspace_root().join("Cargo.toml");
+        let mut inherited = HashMap::new();
+
+        let root_doc = self.manifests.stage(&root_manifest)?;
+        if let Some(deps) = root_doc
+            .get_mut("workspace")
+            .and_then(|v| v.get_mut("dependencies"))
+            .and_then(|v| v.as_table_like_mut())
+        {
+            for (dep_name, dep_item) in deps.iter_mut() {
+                // Only rewrite path dependencies, as for member manifests below
+                let Some(table) = dep_item.as_table_like_mut() else {
+                    continue;
+                };
+
+                let Some((dependency, version)) =
+                    bumped(dep_name.get(), table, metadata.workspace_root())
+                else {
+                    continue;
+                };
+
+                let current = table.get("version").and_then(|v| v.as_str());
+                let Some(requirement) = rewrite(
+                    current,
+                    &version,
+                    config.workspace(),
+                    None,
+                    dep_name.get(),
+                    &mut notes,
+                ) else {
+                    continue;
+                };
+
+                edits.push(ManifestEdit {
+                    manifest: root_manifest.clone(),
+                    path: vec![
+                        "workspace".into(),
+                        "dependencies".into(),
+                        dep_name.get().to_string(),
+                        "version".into(),
+                    ],
+                    from: current.map(str::to_string),
+                    to: requirement.clone(),
+                    target: EditTarget::Dependency {
+                        dependent: None,
+                        dependency,
+                    },
+                });
+
+                manifest::set_dependency_version(table, requirement.clone());
+                inherited.insert(dep_name.get().to_string(), requirement);
+            }
+        }
+
+        // Every member manifest. Only members are edited, never registry, git
+        // or other path packages
+        for package in metadata.workspace_members() {
+            let path = package.manifest_path.as_std_path();
+            let manifest_dir = path.parent();
+            let package_config = config.package(&package.name);
+
+            let doc = self.manifests.stage(path)?;
+
+            // Inspect all dependency sections that Cargo understands, including
+            // target-specific ones
+            for (section, deps) in manifest::dependency_tables_mut(doc) {
+                for (dep_name, dep_item) in deps.iter_mut() {
+                    // Plain `name = "1.0"` entries are registry dependencies
+                    let Some(table) = dep_item.as_table_like_mut() else {
+                        continue;
+                    };
+
+                    // Entries with `workspace = true` pick up the requirement updated above
+                    let inherits = table
+                        .get("workspace")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+
+                    if inherits {
+                        if let Some(requirement) = inherited.get(dep_name.get()) {
+                            notes.push(Note::Inherited {
+                                dependent: package.name.to_string(),
+                                dependency: dep_name.get().to_string(),
+                                requirement: requirement.clone(),
+                            });
+                        }
+                        continue;
+                    }
+
+                    // Only consider path dependencies on a bumped member, to avoid
+                    // touching registry deps that happen to share a name
+                    let Some((dependency, version)) = manifest_dir
+                        .and_then(|manifest_dir| bumped(dep_name.get(), table, manifest_dir))
+                    else {
+                        continue;
+                    };
+
+                    let current = table.get("version").and_then(|v| v.as_str());
+                    let Some(requirement) = rewrite(
+                        current,
+                        &version,
+                        package_config,
+                        Some(&package.name),
+                        dep_name.get(),
+                        &mut notes,
+                    ) else {
+                        continue;
+                    };
+
+                    let mut keys = section.clone();
+                    keys.extend([dep_name.get().to_string(), "version".into()]);
+
+                    edits.push(ManifestEdit {
+                        manifest: path.to_path_buf(),
+                        path: keys,
+                        from: current.map(str::to_string),
+                        to: requirement.clone(),
+                        target: EditTarget::Dependency {
+                            dependent: Some(package.name.to_string()),
+                            dependency,
+                        },
+                    });
+
+                    // Mutate only the version field, preserving path, features, etc.
+                    manifest::set_dependency_version(table, requirement);
+                }
+            }
+        }
+
+        self.edits.extend(edits);
+        self.notes.extend(notes);
+        Ok(())
+    }
+}
+
+/// Bring `Cargo.lock` up to date with the manifests, without network access.
+fn cargo_update(root: &Path) -> Result<()> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let status = Command::new(cargo)
+        .args(["update", "--workspace", "--offline"])
+        .current_dir(root)
+        .status()
+        .context("failed to run `cargo update`")?;
+
+    if !status.success() {
+        bail!("`cargo update --workspace --offline` failed ({status})");
+    }
+
+    Ok(())
+}
+
+/// Restore manifests written before `err`, and describe what was undone.
+fn rollback(written: &[(&PathBuf, &Staged)], err: anyhow::Error) -> anyhow::Error {
+    let mut restored = Vec::new();
+    let mut failed = Vec::new();
+
+    for (path, staged) in written.iter().rev() {
+        match manifest::replace(path, &staged.original) {
+            Ok(()) => restored.push(format!("`{}`", path.display())),
+            Err(e) => failed.push(format!("`{}` ({e:#})", path.display())),
+        }
+    }
+
+    let message = match (restored.is_empty(), failed.is_empty()) {
+        (true, true) => "aborted, no manifest was changed".to_string(),
+        (false, true) => format!("aborted, restored {}", restored.join(", ")),
+        (_, false) => format!(
+            "aborted and could not restore {}; the workspace is partially updated",
+            failed.join(", ")
+        ),
+    };
+
+    err.context(message)
+}
+
+/// Where each member lives, to check that path dependencies point at it.
+fn member_dirs(metadata: &Metadata) -> HashMap<String, PathBuf> {
+    metadata
+        .workspace_members()
+        .into_iter()
+        .filter_map(|package| {
+            let dir = package.manifest_path.parent()?.as_std_path().to_path_buf();
+            Some((package.name.to_string(), dir))
+        })
+        .collect()
+}
+
+/// Requirements of workspace members on bumped packages that their new
+/// versions no longer satisfy.
+fn broken_dependents(
+    metadata: &Metadata,
+    member_dirs: &HashMap<String, PathBuf>,
+    updated: &HashMap<String, SemanticVersion>,
+) -> Vec<Note> {
+    let mut broken = Vec::new();
+
+    for member in metadata.workspace_members() {
+        for dep in &member.dependencies {
+            let Some(version) = updated.get(&dep.name) else {
+                continue;
+            };
+
+            // only path dependencies on the bumped member itself
+            let on_member = dep
+                .path
+                .as_ref()
+                .zip(member_dirs.get(&dep.name))
+                .is_some_and(|(path, dir)| path.as_std_path() == dir);
+
+            // a path dependency without a `version` accepts whatever is there
+            if !on_member || dep.req == VersionReq::STAR || dep.req.matches(version.version()) {
+                continue;
+            }
+
+            broken.push(Note::Broken {
+                dependent: member.name.to_string(),
+                dependency: dep.name.clone(),
+                requirement: dep.req.to_string(),
+                kind: dep.kind,
+                version: version.clone(),
+            });
+        }
+    }
+
+    broken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::Workspace, version::semantic_version::ReleaseLevel};
+
+    fn workspace() -> Workspace {
+        let workspace = Workspace::members(&["core", "app"]);
+        workspace.member("core", "1.2.0", "").member(
+            "app",
+            "0.1.0",
+            "\n[target.'cfg(unix)'.dependencies]\ncore = { path = \"../core\", version = \"^1.2\" }\n",
+        );
+        workspace
+    }
+
+    fn read(workspace: &Workspace, relative: &str) -> String {
+        std::fs::read_to_string(workspace.path(relative)).unwrap()
+    }
+
+    fn plan(workspace: &Workspace, level: ReleaseLevel, options: BumpOptions) -> Result<BumpPlan> {
+        let metadata = workspace.metadata();
+        let config = WorkspaceConfig::load(None, workspace.root(), &metadata)?;
+        let core = metadata.select_packages(false, &["core".to_string()])?[0];
+        let transition = TransitionInput::BumpRelease {
+            level,
+            metadata: None,
+        };
+
+        BumpPlan::new(&metadata, &config, &[(core, transition)], &options)
+    }
+
+    #[test]
+    fn plans_without_writing() {
+        let workspace = workspace();
+
+        let plan = plan(
+            &workspace,
+            ReleaseLevel::Major,
+            BumpOptions {
+                propagation: Some(Propagation::Always),
+                cascade: false,
+                ..BumpOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(plan.packages().len(), 1);
+        assert_eq!(plan.packages()[0].to.to_string(), "2.0.0");
+
+        let edit = &plan.edits()[1];
+        assert_eq!(
+            edit.path,
+            ["target", "cfg(unix)", "dependencies", "core", "version"]
+        );
+        assert_eq!(edit.from.as_deref(), Some("^1.2"));
+        assert_eq!(edit.to, "^2.0");
+        assert_eq!(edit.manifest, workspace.path("app/Cargo.toml"));
+
+        let core = std::fs::read_to_string(workspace.path("core/Cargo.toml")).unwrap();
+        assert!(core.contains("1.2.0"));
+
+        plan.apply().unwrap();
+
+        let core = std::fs::read_to_string(workspace.path("core/Cargo.toml")).unwrap();
+        assert!(core.contains("2.0.0"));
+    }
+
+    #[test]
+    fn diffs_changed_manifests() {
+        let workspace = workspace();
+
+        let plan = plan(&workspace, ReleaseLevel::Minor, BumpOptions::default()).unwrap();
+
+        assert_eq!(
+            plan.diff(),
+            "--- a/core/Cargo.toml\n\
+             +++ b/core/Cargo.toml\n\
+             @@ -1,4 +1,4 @@\n \
+             [package]\n \
+             name = \"core\"\n\
+             -version = \"1.2.0\"\n\
+             +version = \"1.3.0\"\n \
+             edition = \"2021\"\n"
+        );
+    }
+
+    #[test]
+    fn diff_matches_what_is_written_for_crlf() {
+        let workspace = workspace();
+        let core = read(&workspace, "core/Cargo.toml").replace('\n', "\r\n");
+        workspace.file("core/Cargo.toml", &core);
+
+        let plan = plan(&workspace, ReleaseLevel::Minor, BumpOptions::default()).unwrap();
+
+        let diff = plan.diff();
+        let changed: Vec<&str> = diff
+            .lines()
+            .filter(|line| !line.starts_with("---") && !line.starts_with("+++"))
+            .filter(|line| line.starts_with('-') || line.starts_with('+'))
+            .collect();
+        assert_eq!(changed, ["-version = \"1.2.0\"", "+version = \"1.3.0\""]);
+
+        plan.apply().unwrap();
+
+        assert_eq!(
+            read(&workspace, "core/Cargo.toml"),
+            core.replace("1.2.0", "1.3.0")
+        );
+    }
+
+    #[test]
+    fn updates_lockfile_entries() {
+        let workspace = workspace();
+        workspace.file(
+            "Cargo.lock",
+            r#"version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "core 1.2.0",
+ "core 1.0.0",
+]
+
+[[package]]
+name = "core"
+version = "1.2.0"
+
+[[package]]
+name = "core"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "0000"
+"#,
+        );
+
+        let plan = plan(&workspace, ReleaseLevel::Minor, BumpOptions::default()).unwrap();
+
+        let edit = plan.edits().last().unwrap();
+        assert_eq!(edit.path, ["package", "1", "version"]);
+        assert_eq!(
+            edit.target,
+            EditTarget::Lockfile {
+                package: "core".into()
+            }
+        );
+
+        plan.apply().unwrap();
+
+        let lock = std::fs::read_to_string(workspace.path("Cargo.lock")).unwrap();
+        assert!(
+            lock.contains(" \"core 1.3.0\",\n \"core 1.0.0\",\n"),
+            "{lock}"
+        );
+        assert!(
+            lock.contains("name = \"core\"\nversion = \"1.3.0\"\n"),
+            "{lock}"
+        );
+        assert!(lock.contains("version = \"1.0.0\"\nsource"), "{lock}");
+    }
+
+    #[test]
+    fn rolls_back_on_failed_write() {
+        let workspace = workspace();
+
+        let plan = plan(
+            &workspace,
+            ReleaseLevel::Major,
+            BumpOptions {
+                propagation: Some(Propagation::Always),
+                cascade: false,
+                ..BumpOptions::default()
+            },
+        )
+        .unwrap();
+
+        // `app` is written before `core`, which can no longer be replaced
+        let app = std::fs::read_to_string(workspace.path("app/Cargo.toml")).unwrap();
+        std::fs::remove_file(workspace.path("core/Cargo.toml")).unwrap();
+        std::fs::create_dir(workspace.path("core/Cargo.toml")).unwrap();
+
+        let err = plan.apply().unwrap_err();
+
+        assert!(err.to_string().contains("restored"), "{err:#}");
+        assert!(err.to_string().contains("app"), "{err:#}");
+        assert_eq!(
+            std::fs::read_to_string(workspace.path("app/Cargo.toml")).unwrap(),
+            app
+        );
+        assert!(!workspace.path("app/.Cargo.toml.goosectl.tmp").exists());
+    }
+
+    #[test]
+    fn reports_inheriting_dependents_not_cascaded() {
+        let workspace = Workspace::new(
+            "[workspace]\nmembers = [\"core\", \"app\"]\nresolver = \"2\"\n\n[workspace.package]\nversion = \"0.1.0\"\n",
+        );
+        workspace.member("core", "1.2.0", "").package(
+            "app",
+            "[package]\nname = \"app\"\nversion.workspace = true\nedition = \"2021\"\n\n[dependencies]\ncore = { path = \"../core\", version = \"1.2.0\" }\n",
+        );
+
+        let plan = plan(
+            &workspace,
+            ReleaseLevel::Major,
+            BumpOptions {
+                cascade: true,
+                ..BumpOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(plan.packages().len(), 1);
+        assert!(
+            matches!(
+                plan.notes(),
+                [Note::NotCascaded { dependent, causes }, ..] if dependent == "app" && causes == &["core"]
+            ),
+            "{:?}",
+            plan.notes()
+        );
+        assert!(plan.notes()[0].to_string().contains("[workspace.package]"));
+    }
+
+    #[test]
+    fn rollback_restores_exact_bytes() {
+        let workspace = workspace();
+        let app = read(&workspace, "app/Cargo.toml").replace('\n', "\r\n");
+        workspace.file("app/Cargo.toml", &app);
+
+        let plan = plan(
+            &workspace,
+            ReleaseLevel::Major,
+            BumpOptions {
+                propagation: Some(Propagation::Always),
+                ..BumpOptions::default()
+            },
+        )
+        .unwrap();
+
+        std::fs::remove_file(workspace.path("core/Cargo.toml")).unwrap();
+        std::fs::create_dir(workspace.path("core/Cargo.toml")).unwrap();
+
+        plan.apply().unwrap_err();
+
+        assert_eq!(read(&workspace, "app/Cargo.toml"), app);
+    }
+
+    #[test]
+    fn reports_broken_dependents() {
+        let workspace = workspace();
+
+        let plan = plan(&workspace, ReleaseLevel::Major, BumpOptions::default()).unwrap();
+
+        let notes: Vec<String> = plan.notes().iter().map(ToString::to_string).collect();
+        assert_eq!(notes, ["app requires core ^1.2, which is now 2.0.0"]);
+        assert_eq!(plan.edits().len(), 1);
+    }
+
+    #[test]
+    fn notes_satisfied_requirements() {
+        let workspace = workspace();
+
+        let plan = plan(
+            &workspace,
+            ReleaseLevel::Minor,
+            BumpOptions {
+                propagation: Some(Propagation::WhenNeeded),
+                cascade: false,
+                ..BumpOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert!(matches!(plan.notes(), [Note::Satisfied { .. }]));
+        assert_eq!(plan.edits().len(), 1);
+    }
+
+    #[test]
+    fn skips_unchanged_requirements() {
+        let workspace =
+            Workspace::two_members("1.2.0", r#"core = { path = "../core", version = "^1" }"#);
+
+        let plan = plan(
+            &workspace,
+            ReleaseLevel::Minor,
+            BumpOptions {
+                propagation: Some(Propagation::Always),
+                cascade: false,
+                ..BumpOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert!(matches!(plan.notes(), [Note::Satisfied { .. }]));
+        assert!(
+            plan.edits()
+                .iter()
+                .all(|edit| !matches!(edit.target, EditTarget::Dependency { .. }))
+        );
+    }
+}
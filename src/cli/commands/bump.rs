@@ -1,17 +1,468 @@
-use clap::{Args, Subcommand, ValueEnum};
+use std::{collections::HashMap, path::PathBuf};
 
-use crate::version::transition::TransitionInput;
+use anyhow::{Result, bail};
+use cargo_metadata::semver::Version;
+use clap::{Args, CommandFactory, Subcommand, ValueEnum, error::ErrorKind};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::current_version::CurrentVersionRepr;
+use crate::{
+    bump::plan::{
+        BumpOptions, BumpPlan, BumpReason, EditTarget, LockfileUpdate, Note, Propagation,
+    },
+    cli::{CargoGooseCli, global_args::GlobalArgs},
+    config::WorkspaceConfig,
+    version::transition::TransitionInput,
+};
 
 #[derive(Debug, Clone, Args)]
 pub struct BumpArgs {
     #[command(subcommand)]
-    target: VersionBump,
+    target: Option<VersionBump>,
+    #[arg(
+        long,
+        value_name = "POLICY",
+        help = "When to update workspace dependency requirements on bumped packages \
+                [default: always when bumping several packages, never otherwise]"
+    )]
+    pub propagate: Option<Propagation>,
     #[arg(
         long,
-        help = "Do not update workspace dependency versions when bumping a package",
-        default_value = "false"
+        help = "Also bump every workspace member that depends on a bumped package"
     )]
-    pub no_propagate: bool,
+    pub cascade: bool,
+    #[arg(
+        long,
+        help = "With --dry-run, show a unified diff of every manifest that would change"
+    )]
+    pub diff: bool,
+    #[arg(
+        long,
+        value_name = "MODE",
+        default_value = "edit",
+        help = "How to update Cargo.lock for bumped packages"
+    )]
+    pub lockfile: LockfileUpdate,
+    #[arg(short = 'f', long = "format", help = "Output format")]
+    pub format: Option<BumpOutput>,
+}
+
+impl BumpArgs {
+    pub fn execute(
+        &self,
+        metadata: &crate::metadata::Metadata,
+        config: &WorkspaceConfig,
+        global: &GlobalArgs,
+    ) -> Result<()> {
+        if self.diff && !global.dry_run {
+            bail!("`--diff` can only be used with `--dry-run`");
+        }
+
+        if self.diff && matches!(self.format, Some(BumpOutput::Json)) {
+            bail!("`--diff` cannot be combined with `--format json`");
+        }
+
+        // `-p name=bump` gives a package its own transition, others get the
+        // one from the subcommand
+        let mut names = Vec::new();
+        let mut specs = HashMap::new();
+        for arg in &global.package {
+            match arg.split_once('=') {
+                Some((name, spec)) => {
+                    if specs.insert(name.to_string(), parse_spec(spec)?).is_some() {
+                        bail!("`{name}` is given more than one bump with `-p`");
+                    }
+                    names.push(name.to_string());
+                }
+                None => names.push(arg.clone()),
+            }
+        }
+
+        if let Some(name) = global
+            .package
+            .iter()
+            .find(|arg| !arg.contains('=') && specs.contains_key(arg.as_str()))
+        {
+            bail!("`{name}` is selected with `-p` both with and without a bump");
+        }
+
+        let default = self.target.clone().map(TransitionInput::from);
+        if default.is_none() && specs.is_empty() {
+            return Err(missing_subcommand(
+                "a subcommand is required unless every selected package is given \
+                 a bump with `-p <NAME>=<BUMP>`",
+            ));
+        }
+
+        // Determine which packages are being directly bumped
+        let selection = metadata
+            .select_packages(global.workspace, &names)?
+            .into_iter()
+            .map(|package| {
+                let transition = specs
+                    .get(package.name.as_str())
+                    .or(default.as_ref())
+                    .cloned()
+                    .ok_or_else(|| {
+                        missing_subcommand(&format!(
+                            "no bump given for `{0}`: add a subcommand or use `-p {0}=<BUMP>`",
+                            package.name
+                        ))
+                    })?;
+                Ok((package, transition))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Unless asked otherwise, propagation is enabled in workspace mode
+        // (explicit, or implicit when several packages are bumped)
+        let options = BumpOptions {
+            propagation: self
+                .propagate
+                .or(global.workspace.then_some(Propagation::Always)),
+            cascade: self.cascade,
+            lockfile: self.lockfile,
+        };
+
+        let plan = BumpPlan::new(metadata, config, &selection, &options)?;
+
+        let broken: Vec<String> = plan
+            .notes()
+            .iter()
+            .filter(|note| matches!(note, Note::Broken { .. }))
+            .map(|note| format!("  {note}"))
+            .collect();
+
+        if !broken.is_empty() {
+            eprintln!(
+                "warning: these requirements are no longer satisfied \
+                 (use --propagate to update them):\n{}",
+                broken.join("\n")
+            );
+        }
+
+        if !global.dry_run {
+            plan.apply()?;
+        }
+
+        match self.format.as_ref().unwrap_or(&BumpOutput::Plaintext) {
+            BumpOutput::Plaintext => {
+                print_plan(&plan, global);
+
+                if self.diff {
+                    print!("{}", plan.diff());
+                }
+            }
+            BumpOutput::Json => {
+                let report = BumpReport::new(&plan, global.dry_run)?;
+                println!("{}", serde_json::to_string(&report)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A usage error for `bump`, rendered by clap like the ones it raises itself.
+fn missing_subcommand(message: &str) -> anyhow::Error {
+    let mut cli = CargoGooseCli::command();
+    cli.build();
+
+    cli.find_subcommand_mut("goosectl")
+        .and_then(|goosectl| goosectl.find_subcommand_mut("bump"))
+        .expect("`bump` is a subcommand")
+        .error(ErrorKind::MissingSubcommand, message)
+        .into()
+}
+
+/// Print what the plan changes, as prose.
+pub(super) fn print_plan(plan: &BumpPlan, global: &GlobalArgs) {
+    let prefix = if global.dry_run { "[DRY RUN] " } else { "" };
+
+    for edit in plan.edits() {
+        match &edit.target {
+            EditTarget::WorkspaceVersion { members } => println!(
+                "{}Updated workspace package version from {} to {} (inherited by {})",
+                prefix,
+                edit.from.as_deref().unwrap_or_default(),
+                edit.to,
+                members.join(", ")
+            ),
+            EditTarget::Lockfile { package } => println!(
+                "{}Updated package {} in Cargo.lock to {}",
+                prefix, package, edit.to
+            ),
+            EditTarget::PackageVersion { package } => {
+                let Some(bump) = plan.packages().iter().find(|b| b.name == *package) else {
+                    continue;
+                };
+                let cause = match &bump.reason {
+                    BumpReason::Cascaded(causes) => {
+                        format!(" (depends on {})", causes.join(", "))
+                    }
+                    BumpReason::Selected => String::new(),
+                };
+                println!(
+                    "{}Updated package {} from version {} to {}{}",
+                    prefix, bump.name, bump.from, bump.to, cause
+                );
+            }
+            EditTarget::Dependency { dependent, .. } => {
+                // the key the dependency is declared under
+                let key = &edit.path[edit.path.len() - 2];
+                match dependent {
+                    Some(dependent) => println!(
+                        "{}Updated dependency {} in package {} to {}",
+                        prefix, key, dependent, edit.to
+                    ),
+                    None => println!(
+                        "{}Updated dependency {} in [workspace.dependencies] to {}",
+                        prefix, key, edit.to
+                    ),
+                }
+            }
+        }
+    }
+
+    for note in plan.notes() {
+        let location = |dependent: &Option<String>| match dependent {
+            Some(dependent) => format!("package {dependent}"),
+            None => "[workspace.dependencies]".to_string(),
+        };
+
+        match note {
+            Note::Inherited {
+                dependent,
+                dependency,
+                requirement,
+            } => println!(
+                "{}Updated dependency {} in package {} to {} (via [workspace.dependencies])",
+                prefix, dependency, dependent, requirement
+            ),
+            Note::Satisfied {
+                dependent,
+                dependency,
+                requirement,
+                version,
+            } if global.verbose => println!(
+                "{}Skipped dependency {} in {}: {} still satisfies {}",
+                prefix,
+                dependency,
+                location(dependent),
+                version,
+                requirement
+            ),
+            Note::Kept {
+                dependent,
+                dependency,
+                requirement,
+                version,
+            } => println!(
+                "{}Kept dependency {} in {} at {}: cannot retarget it to {}",
+                prefix,
+                dependency,
+                location(dependent),
+                requirement,
+                version
+            ),
+            Note::NotCascaded { dependent, causes } => println!(
+                "{}Skipped package {} (depends on {}): it inherits its version from \
+                 [workspace.package]",
+                prefix,
+                dependent,
+                causes.join(", ")
+            ),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum BumpOutput {
+    Plaintext,
+    Json,
+}
+
+/// What a bump did, or would do with `--dry-run`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BumpReport {
+    dry_run: bool,
+    packages: Vec<PackageBumpRepr>,
+    edits: Vec<ManifestEditRepr>,
+    /// Requirements left unsatisfied because they were not propagated, and
+    /// dependents left unbumped because they inherit their version.
+    warnings: Vec<String>,
+}
+
+impl BumpReport {
+    fn new(plan: &BumpPlan, dry_run: bool) -> Result<Self> {
+        let packages = plan
+            .packages()
+            .iter()
+            .map(|bump| {
+                Ok(PackageBumpRepr {
+                    package: bump.name.clone(),
+                    from: CurrentVersionRepr::try_from(bump.from.clone())?,
+                    to: CurrentVersionRepr::try_from(bump.to.clone())?,
+                    transition: bump.transition.clone().into(),
+                    inherited: bump.inherited,
+                    cascaded_from: match &bump.reason {
+                        BumpReason::Cascaded(causes) => causes.clone(),
+                        BumpReason::Selected => Vec::new(),
+                    },
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let edits = plan
+            .edits()
+            .iter()
+            .map(|edit| ManifestEditRepr {
+                manifest: edit.manifest.clone(),
+                path: edit.path.clone(),
+                from: edit.from.clone(),
+                to: edit.to.clone(),
+                target: match &edit.target {
+                    EditTarget::PackageVersion { package } => EditTargetRepr::PackageVersion {
+                        package: package.clone(),
+                    },
+                    EditTarget::WorkspaceVersion { members } => EditTargetRepr::WorkspaceVersion {
+                        members: members.clone(),
+                    },
+                    EditTarget::Lockfile { package } => EditTargetRepr::Lockfile {
+                        package: package.clone(),
+                    },
+                    EditTarget::Dependency {
+                        dependent,
+                        dependency,
+                    } => EditTargetRepr::Dependency {
+                        dependent: dependent.clone(),
+                        dependency: dependency.clone(),
+                    },
+                },
+            })
+            .collect();
+
+        let warnings = plan
+            .notes()
+            .iter()
+            .filter(|note| matches!(note, Note::Broken { .. } | Note::NotCascaded { .. }))
+            .map(ToString::to_string)
+            .collect();
+
+        Ok(Self {
+            dry_run,
+            packages,
+            edits,
+            warnings,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PackageBumpRepr {
+    package: String,
+    from: CurrentVersionRepr,
+    to: CurrentVersionRepr,
+    transition: TransitionRepr,
+    /// Whether the version is inherited from `[workspace.package]`.
+    inherited: bool,
+    /// The bumped dependencies that caused a cascaded bump, empty for
+    /// selected packages.
+    cascaded_from: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TransitionRepr {
+    StartPrerelease {
+        level: ReleaseLevel,
+        pre: String,
+        metadata: Option<String>,
+    },
+    IncrementPrerelease {
+        metadata: Option<String>,
+    },
+    TransitionPrerelease {
+        pre: String,
+        metadata: Option<String>,
+    },
+    FinalizeRelease {
+        metadata: Option<String>,
+    },
+    BumpRelease {
+        level: ReleaseLevel,
+        metadata: Option<String>,
+    },
+    Set {
+        version: String,
+        allow_downgrade: bool,
+    },
+}
+
+impl From<TransitionInput> for TransitionRepr {
+    fn from(transition: TransitionInput) -> Self {
+        match transition {
+            TransitionInput::StartPrerelease {
+                level,
+                pre,
+                metadata,
+            } => Self::StartPrerelease {
+                level: level.into(),
+                pre,
+                metadata,
+            },
+            TransitionInput::IncrementPrerelease { metadata } => {
+                Self::IncrementPrerelease { metadata }
+            }
+            TransitionInput::TransitionPrerelease { pre, metadata } => {
+                Self::TransitionPrerelease { pre, metadata }
+            }
+            TransitionInput::FinalizeRelease { metadata } => Self::FinalizeRelease { metadata },
+            TransitionInput::BumpRelease { level, metadata } => Self::BumpRelease {
+                level: level.into(),
+                metadata,
+            },
+            TransitionInput::Set {
+                version,
+                allow_downgrade,
+            } => Self::Set {
+                version: version.to_string(),
+                allow_downgrade,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ManifestEditRepr {
+    manifest: PathBuf,
+    /// Keys leading to the edited value.
+    path: Vec<String>,
+    from: Option<String>,
+    to: String,
+    #[serde(flatten)]
+    target: EditTargetRepr,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EditTargetRepr {
+    PackageVersion {
+        package: String,
+    },
+    WorkspaceVersion {
+        members: Vec<String>,
+    },
+    Lockfile {
+        package: String,
+    },
+    /// A requirement in a member manifest, or in `[workspace.dependencies]`
+    /// when `dependent` is null.
+    Dependency {
+        dependent: Option<String>,
+        dependency: String,
+    },
 }
 
 #[derive(Debug, Clone, Subcommand)]
@@ -41,11 +492,17 @@ pub enum VersionBump {
         #[arg(long, help = "Build metadata")]
         metadata: Option<String>,
     },
+    Set {
+        #[arg(help = "The version to set (e.g. `2.0.0` or `2.0.0-rc.1`)")]
+        version: Version,
+        #[arg(long, help = "Allow setting a version lower than the current one")]
+        allow_downgrade: bool,
+    },
 }
 
-impl From<BumpArgs> for TransitionInput {
-    fn from(args: BumpArgs) -> TransitionInput {
-        match args.target {
+impl From<VersionBump> for TransitionInput {
+    fn from(val: VersionBump) -> TransitionInput {
+        match val {
             VersionBump::Prerelease { pre, metadata } => {
                 match pre {
                     // graduate pre-release to another pre-release (e.g., alpha -> beta)
@@ -73,17 +530,79 @@ impl From<BumpArgs> for TransitionInput {
                     metadata,
                 },
             },
+            VersionBump::Set {
+                version,
+                allow_downgrade,
+            } => TransitionInput::Set {
+                version,
+                allow_downgrade,
+            },
         }
     }
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+/// Parse the bump of a `-p name=bump` spec: a release level, optionally
+/// starting a prerelease (`minor`, `minor:rc`), `prerelease` optionally moving
+/// to another identifier (`prerelease:beta`), or `release`.
+fn parse_spec(spec: &str) -> Result<TransitionInput> {
+    let (kind, pre) = match spec.split_once(':') {
+        Some((kind, pre)) if !pre.is_empty() => (kind, Some(pre.to_string())),
+        Some(_) => bail!("invalid bump `{spec}`: missing prerelease identifier after `:`"),
+        None => (spec, None),
+    };
+
+    let transition = match (kind, pre) {
+        ("release", None) => TransitionInput::FinalizeRelease { metadata: None },
+        ("prerelease", None) => TransitionInput::IncrementPrerelease { metadata: None },
+        ("prerelease", Some(pre)) => TransitionInput::TransitionPrerelease {
+            pre,
+            metadata: None,
+        },
+        (level, pre) => {
+            let Ok(level) = ReleaseLevel::from_str(level, true) else {
+                bail!(
+                    "invalid bump `{spec}` (expected e.g. `minor`, `minor:rc`, `prerelease`, \
+                     `prerelease:beta` or `release`)"
+                );
+            };
+
+            match pre {
+                Some(pre) => TransitionInput::StartPrerelease {
+                    level: level.into(),
+                    pre,
+                    metadata: None,
+                },
+                None => TransitionInput::BumpRelease {
+                    level: level.into(),
+                    metadata: None,
+                },
+            }
+        }
+    };
+
+    Ok(transition)
+}
+
+#[derive(Debug, Clone, ValueEnum, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
 pub enum ReleaseLevel {
     Patch,
     Minor,
     Major,
 }
 
+impl From<crate::version::semantic_version::ReleaseLevel> for ReleaseLevel {
+    fn from(val: crate::version::semantic_version::ReleaseLevel) -> Self {
+        use crate::version::semantic_version::ReleaseLevel as Level;
+
+        match val {
+            Level::Patch => Self::Patch,
+            Level::Minor => Self::Minor,
+            Level::Major => Self::Major,
+        }
+    }
+}
+
 impl From<ReleaseLevel> for crate::version::semantic_version::ReleaseLevel {
     fn from(val: ReleaseLevel) -> Self {
         match val {
@@ -93,3 +612,100 @@ impl From<ReleaseLevel> for crate::version::semantic_version::ReleaseLevel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Workspace;
+
+    #[test]
+    fn report_lists_versions_and_edits() {
+        let workspace =
+            Workspace::new("[workspace]\nmembers = [\"core\", \"app\"]\nresolver = \"2\"\n");
+        workspace
+            .package(
+                "core",
+                "[package]\nname = \"core\"\nversion = \"1.2.0\"\nedition = \"2021\"\n",
+            )
+            .package(
+                "app",
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\ncore = { path = \"../core\", version = \"1.2.0\" }\n",
+            );
+
+        let metadata = workspace.metadata();
+        let config = WorkspaceConfig::load(None, workspace.root(), &metadata).unwrap();
+        let core = metadata
+            .select_packages(false, &["core".to_string()])
+            .unwrap()[0];
+
+        let plan = BumpPlan::new(
+            &metadata,
+            &config,
+            &[(core, parse_spec("minor:rc").unwrap())],
+            &BumpOptions {
+                propagation: Some(Propagation::Always),
+                cascade: false,
+                ..BumpOptions::default()
+            },
+        )
+        .unwrap();
+
+        let report = serde_json::to_value(BumpReport::new(&plan, true).unwrap()).unwrap();
+
+        assert_eq!(report["dry_run"], true);
+        assert_eq!(report["packages"][0]["from"]["version"], "1.2.0");
+        assert_eq!(report["packages"][0]["to"]["pre"], "rc");
+        assert_eq!(
+            report["packages"][0]["transition"],
+            serde_json::json!({
+                "kind": "start_prerelease",
+                "level": "minor",
+                "pre": "rc",
+                "metadata": null,
+            })
+        );
+        assert_eq!(report["edits"][1]["kind"], "dependency");
+        assert_eq!(report["edits"][1]["dependent"], "app");
+        assert_eq!(
+            report["edits"][1]["path"],
+            serde_json::json!(["dependencies", "core", "version"])
+        );
+        assert_eq!(report["edits"][1]["to"], "1.3.0-rc.1");
+    }
+
+    #[test]
+    fn parses_specs() {
+        use crate::version::semantic_version::ReleaseLevel as Level;
+
+        assert_eq!(
+            parse_spec("patch").unwrap(),
+            TransitionInput::BumpRelease {
+                level: Level::Patch,
+                metadata: None
+            }
+        );
+        assert_eq!(
+            parse_spec("major:alpha").unwrap(),
+            TransitionInput::StartPrerelease {
+                level: Level::Major,
+                pre: "alpha".into(),
+                metadata: None
+            }
+        );
+        assert_eq!(
+            parse_spec("prerelease:beta").unwrap(),
+            TransitionInput::TransitionPrerelease {
+                pre: "beta".into(),
+                metadata: None
+            }
+        );
+        assert_eq!(
+            parse_spec("release").unwrap(),
+            TransitionInput::FinalizeRelease { metadata: None }
+        );
+
+        assert!(parse_spec("minor:").is_err());
+        assert!(parse_spec("release:rc").is_err());
+        assert!(parse_spec("huge").is_err());
+    }
+}
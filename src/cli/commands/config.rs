@@ -0,0 +1,237 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Subcommand, ValueEnum};
+
+use crate::{
+    cli::global_args::GlobalArgs,
+    config::{
+        CURRENT_VERSION, MANIFEST_KEY, Provenance, WorkspaceConfig, config_file, migrate_document,
+        validate,
+    },
+};
+
+const WORKSPACE_TABLE: &[&str] = &["workspace", "metadata", MANIFEST_KEY];
+const PACKAGE_TABLE: &[&str] = &["package", "metadata", MANIFEST_KEY];
+
+#[derive(Debug, Clone, Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigCommand {
+    Show {
+        #[arg(short = 'f', long = "format", help = "Output format")]
+        format: Option<ConfigOutput>,
+    },
+    Validate {
+        #[arg(
+            help = "Config file to check (defaults to every configuration source in the workspace)",
+            value_name = "PATH"
+        )]
+        path: Option<PathBuf>,
+    },
+    Migrate {
+        #[arg(
+            help = "Config file to migrate (defaults to every configuration source in the workspace)",
+            value_name = "PATH"
+        )]
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum ConfigOutput {
+    Toml,
+    Json,
+}
+
+impl ConfigArgs {
+    /// `load` is only called for `show`: `validate` and `migrate` read the
+    /// sources themselves, so they work even when loading would fail.
+    pub fn execute(
+        &self,
+        metadata: &crate::metadata::Metadata,
+        cwd: &Path,
+        load: impl FnOnce() -> Result<WorkspaceConfig>,
+        global: &GlobalArgs,
+    ) -> Result<()> {
+        match &self.command {
+            ConfigCommand::Show { format } => show(metadata, &load()?, global, format.as_ref()),
+            ConfigCommand::Validate { path } => {
+                let file = config_file(global.config.as_deref(), cwd, metadata.workspace_root())?;
+                check(metadata, file.as_deref(), path.as_deref())
+            }
+            ConfigCommand::Migrate { path } => {
+                let file = config_file(global.config.as_deref(), cwd, metadata.workspace_root())?;
+                migrate(metadata, file.as_deref(), global, path.as_deref())
+            }
+        }
+    }
+}
+
+fn show(
+    metadata: &crate::metadata::Metadata,
+    config: &WorkspaceConfig,
+    global: &GlobalArgs,
+    format: Option<&ConfigOutput>,
+) -> Result<()> {
+    let package = match global.package.as_slice() {
+        [] => None,
+        [name] => {
+            // make sure the package exists before falling back to the workspace
+            metadata.select_packages(false, &global.package)?;
+            Some(name.as_str())
+        }
+        _ => bail!("`config show` accepts at most one --package"),
+    };
+
+    let provenance = Provenance::of(config.figment(package))?;
+
+    match format.unwrap_or(&ConfigOutput::Toml) {
+        ConfigOutput::Toml => print!("{}", provenance.to_toml()?),
+        ConfigOutput::Json => println!("{}", serde_json::to_string_pretty(&provenance.to_json())?),
+    }
+
+    Ok(())
+}
+
+fn check(
+    metadata: &crate::metadata::Metadata,
+    config_file: Option<&Path>,
+    path: Option<&Path>,
+) -> Result<()> {
+    // (label, raw configuration) for every source we were asked to check
+    let mut sources = Vec::new();
+
+    match path {
+        Some(path) => sources.push((path.display().to_string(), read_toml(path)?)),
+        None => {
+            let root_manifest = metadata.workspace_root().join("Cargo.toml");
+            if let Some(table) = metadata.workspace_metadata().get(MANIFEST_KEY) {
+                sources.push((
+                    format!("{} [workspace.metadata.goosectl]", root_manifest.display()),
+                    table.clone(),
+                ));
+            }
+
+            if let Some(file) = config_file {
+                sources.push((file.display().to_string(), read_toml(file)?));
+            }
+
+            for package in metadata.workspace_members() {
+                if let Some(table) = package.metadata.get(MANIFEST_KEY) {
+                    sources.push((
+                        format!("{} [package.metadata.goosectl]", package.manifest_path),
+                        table.clone(),
+                    ));
+                }
+            }
+        }
+    }
+
+    if sources.is_empty() {
+        println!("No configuration found; using defaults.");
+        return Ok(());
+    }
+
+    let mut invalid = 0;
+    for (label, value) in &sources {
+        let errors = validate(value);
+
+        if errors.is_empty() {
+            println!("{label}: ok");
+            continue;
+        }
+
+        invalid += 1;
+        for error in errors {
+            println!("{label}: {error}");
+        }
+    }
+
+    if invalid > 0 {
+        bail!("{invalid} configuration source(s) failed validation");
+    }
+
+    Ok(())
+}
+
+fn migrate(
+    metadata: &crate::metadata::Metadata,
+    config_file: Option<&Path>,
+    global: &GlobalArgs,
+    path: Option<&Path>,
+) -> Result<()> {
+    // file -> goosectl tables within it (empty key path for a standalone file)
+    let mut files: Vec<(PathBuf, Vec<&[&str]>)> = Vec::new();
+
+    match path {
+        Some(path) => files.push((path.to_path_buf(), vec![&[]])),
+        None => {
+            if metadata.workspace_metadata().get(MANIFEST_KEY).is_some() {
+                let root_manifest = metadata.workspace_root().join("Cargo.toml");
+                files.push((root_manifest, vec![WORKSPACE_TABLE]));
+            }
+
+            if let Some(file) = config_file {
+                files.push((file.to_path_buf(), vec![&[]]));
+            }
+
+            for package in metadata.workspace_members() {
+                if package.metadata.get(MANIFEST_KEY).is_none() {
+                    continue;
+                }
+
+                let manifest = package.manifest_path.clone().into_std_path_buf();
+                match files.iter_mut().find(|(file, _)| file == &manifest) {
+                    // a root package shares its manifest with the workspace table
+                    Some((_, tables)) => tables.push(PACKAGE_TABLE),
+                    None => files.push((manifest, vec![PACKAGE_TABLE])),
+                }
+            }
+        }
+    }
+
+    let prefix = if global.dry_run { "[DRY RUN] " } else { "" };
+
+    for (file, tables) in files {
+        let contents = std::fs::read_to_string(&file)
+            .with_context(|| format!("failed to read `{}`", file.display()))?;
+        let mut doc = contents
+            .parse::<toml_edit::DocumentMut>()
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        let mut changed = false;
+
+        for table in tables {
+            let label = match table.is_empty() {
+                true => file.display().to_string(),
+                false => format!("{} [{}]", file.display(), table.join(".")),
+            };
+
+            match migrate_document(&mut doc, table).with_context(|| label.clone())? {
+                Some(from) => {
+                    println!("{prefix}Migrated {label} from version {from} to {CURRENT_VERSION}");
+                    changed = true;
+                }
+                None => println!("{label} is already at version {CURRENT_VERSION}"),
+            }
+        }
+
+        if changed && !global.dry_run {
+            std::fs::write(&file, doc.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_toml(path: &Path) -> Result<serde_json::Value> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read `{}`", path.display()))?;
+
+    toml_edit::de::from_str(&contents)
+        .with_context(|| format!("failed to parse `{}`", path.display()))
+}
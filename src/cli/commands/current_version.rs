@@ -2,9 +2,10 @@ use anyhow::Result;
 use clap::{Args, ValueEnum};
 
 use crate::{
-    cli::global_args::GlobalArgs, utils::select_single_version,
+    cli::global_args::GlobalArgs, config::WorkspaceConfig, utils::select_single_version,
     version::semantic_version::SemanticVersion,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Args)]
@@ -16,8 +17,17 @@ pub struct CurrentVersionArgs {
 }
 
 impl CurrentVersionArgs {
-    pub fn execute(&self, metadata: &crate::metadata::Metadata, global: &GlobalArgs) -> Result<()> {
+    pub fn execute(
+        &self,
+        metadata: &crate::metadata::Metadata,
+        config: &WorkspaceConfig,
+        global: &GlobalArgs,
+    ) -> Result<()> {
         let packages = metadata.select_packages(global.workspace, global.package.as_slice())?;
+        let versions = packages
+            .iter()
+            .map(|p| config.package_version(p))
+            .collect::<Result<Vec<SemanticVersion>>>()?;
 
         let format = self
             .format
@@ -25,7 +35,7 @@ impl CurrentVersionArgs {
             .unwrap_or(&CurrentVersionOutput::Plaintext);
 
         if self.force_single_version {
-            let version = select_single_version(packages.iter().map(|p| p.version.clone()))?;
+            let version = select_single_version(versions)?;
 
             match format {
                 CurrentVersionOutput::Plaintext => println!("{version}"),
@@ -41,15 +51,14 @@ impl CurrentVersionArgs {
         match format {
             CurrentVersionOutput::Plaintext => {
                 // plaintext stays strict
-                let version = select_single_version(packages.iter().map(|p| p.version.clone()))?;
+                let version = select_single_version(versions)?;
                 println!("{version}");
             }
 
             CurrentVersionOutput::Json => {
                 let mut out = Vec::new();
 
-                for pkg in &packages {
-                    let version: SemanticVersion = pkg.version.clone().try_into()?;
+                for (pkg, version) in packages.iter().zip(versions) {
                     out.push(PackageVersionRepr {
                         package: pkg.name.to_string(),
                         version: CurrentVersionRepr::try_from(version)?,
@@ -84,7 +93,7 @@ pub struct PackageVersionRepr {
     version: CurrentVersionRepr,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CurrentVersionRepr {
     version: String,
     major: u64,
@@ -137,7 +146,11 @@ mod force_single_tests {
     }
 
     fn sv(s: &str) -> SemanticVersion {
-        SemanticVersion::try_from(Version::parse(s).unwrap()).unwrap()
+        SemanticVersion::parse_with(
+            Version::parse(s).unwrap(),
+            crate::version::scheme::STRICT.clone(),
+        )
+        .unwrap()
     }
 
     #[test]
@@ -181,7 +194,11 @@ mod tests {
     use cargo_metadata::semver::Version;
 
     fn sv(s: &str) -> SemanticVersion {
-        SemanticVersion::try_from(Version::parse(s).unwrap()).unwrap()
+        SemanticVersion::parse_with(
+            Version::parse(s).unwrap(),
+            crate::version::scheme::STRICT.clone(),
+        )
+        .unwrap()
     }
 
     #[test]
@@ -0,0 +1,346 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result, bail};
+use cargo_metadata::Package;
+use clap::Args;
+
+use crate::{
+    cli::global_args::GlobalArgs,
+    config::{CONFIG_FILE_NAME, CURRENT_VERSION, MANIFEST_KEY, VersioningMode},
+};
+
+#[derive(Debug, Clone, Args)]
+pub struct InitArgs {
+    #[arg(
+        long,
+        help = "Write to `[workspace.metadata.goosectl]` in the root Cargo.toml instead of goosectl.toml"
+    )]
+    manifest: bool,
+    #[arg(long, help = "Overwrite an existing configuration")]
+    force: bool,
+}
+
+impl InitArgs {
+    pub fn execute(&self, metadata: &crate::metadata::Metadata, global: &GlobalArgs) -> Result<()> {
+        let survey = Survey::new(&metadata.workspace_members());
+        let root = metadata.workspace_root();
+
+        let manifest = root.join("Cargo.toml");
+        let file = root.join(CONFIG_FILE_NAME);
+
+        // settings in one place would be shadowed by, or shadow, those in the other
+        let (path, contents) = if self.manifest {
+            if file.exists() && !self.force {
+                bail!(
+                    "`{}` already exists; use --force to configure the manifest anyway",
+                    file.display()
+                );
+            }
+
+            let contents = std::fs::read_to_string(&manifest)
+                .with_context(|| format!("failed to read `{}`", manifest.display()))?;
+            let contents = self.embed_in_manifest(&contents, &survey)?;
+            (manifest, contents)
+        } else {
+            if file.exists() && !self.force {
+                bail!(
+                    "`{}` already exists; use --force to overwrite it",
+                    file.display()
+                );
+            }
+
+            let doc = crate::manifest::read(&manifest)?;
+            if let Some(parent) = ["workspace", "package"].into_iter().find(|parent| {
+                doc.get(parent)
+                    .and_then(|t| t.get("metadata"))
+                    .and_then(|t| t.get(MANIFEST_KEY))
+                    .is_some()
+            }) && !self.force
+            {
+                bail!(
+                    "`[{parent}.metadata.{MANIFEST_KEY}]` already exists in `{}`; use --force to write {CONFIG_FILE_NAME} anyway",
+                    manifest.display()
+                );
+            }
+
+            (file, survey.render(None))
+        };
+
+        if global.dry_run {
+            println!("[DRY RUN] Would write {}:\n", path.display());
+            print!("{contents}");
+            return Ok(());
+        }
+
+        std::fs::write(&path, contents)?;
+        println!(
+            "Wrote {} ({} versioning)",
+            path.display(),
+            survey.mode_name()
+        );
+
+        Ok(())
+    }
+
+    fn embed_in_manifest(&self, contents: &str, survey: &Survey) -> Result<String> {
+        let mut doc = contents.parse::<toml_edit::DocumentMut>()?;
+
+        // a lone package has no [workspace] table to hang settings off
+        let parent = match doc.contains_table("workspace") {
+            true => "workspace",
+            false => "package",
+        };
+
+        let existing = doc
+            .get_mut(parent)
+            .and_then(|t| t.get_mut("metadata"))
+            .and_then(|t| t.as_table_like_mut());
+
+        if let Some(table) = existing
+            && table.contains_key(MANIFEST_KEY)
+        {
+            if !self.force {
+                bail!(
+                    "`[{parent}.metadata.{MANIFEST_KEY}]` already exists; use --force to overwrite it"
+                );
+            }
+            table.remove(MANIFEST_KEY);
+        }
+
+        let header = format!("{parent}.metadata.{MANIFEST_KEY}");
+        let mut out = doc.to_string();
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push('\n');
+        out.push_str(&survey.render(Some(&header)));
+
+        // appending headers can clash with an inline `metadata = { ... }` table
+        out.parse::<toml_edit::DocumentMut>()
+            .with_context(|| format!("cannot add `[{header}]` to this manifest"))?;
+
+        Ok(out)
+    }
+}
+
+/// What `init` learned about the workspace.
+struct Survey {
+    /// (name, version, workspace members it depends on by path)
+    members: Vec<(String, String, BTreeSet<String>)>,
+    mode: VersioningMode,
+}
+
+impl Survey {
+    fn new(packages: &[&Package]) -> Self {
+        let names: BTreeSet<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+
+        let members = packages
+            .iter()
+            .map(|p| {
+                let deps = p
+                    .dependencies
+                    .iter()
+                    .filter(|d| d.path.is_some() && names.contains(d.name.as_str()))
+                    .map(|d| d.name.clone())
+                    .collect();
+                (p.name.to_string(), p.version.to_string(), deps)
+            })
+            .collect::<Vec<_>>();
+
+        let versions: BTreeSet<&str> = members.iter().map(|(_, v, _)| v.as_str()).collect();
+        let mode = match (members.len(), versions.len()) {
+            (2.., 1) => VersioningMode::Lockstep,
+            _ => VersioningMode::Independent,
+        };
+
+        Self { members, mode }
+    }
+
+    fn mode_name(&self) -> &'static str {
+        match self.mode {
+            VersioningMode::Independent => "independent",
+            VersioningMode::Lockstep => "lockstep",
+        }
+    }
+
+    /// Render the configuration, under `[header]` when embedded in a manifest.
+    fn render(&self, header: Option<&str>) -> String {
+        let mut out = String::new();
+        let section = |name: &str| match header {
+            Some(header) => format!("[{header}.{name}]\n"),
+            None => format!("[{name}]\n"),
+        };
+
+        out.push_str("# goosectl configuration, generated by `cargo goosectl init`.\n");
+        out.push_str(
+            "# Schema: https://github.com/mozilla-ai/cargo-goosectl/blob/main/schemas/goosectl.schema.json\n",
+        );
+        if let Some(header) = header {
+            out.push_str(&format!("[{header}]\n"));
+        }
+        out.push('\n');
+        out.push_str("# Configuration format version.\n");
+        out.push_str(&format!("version = {CURRENT_VERSION}\n\n"));
+
+        out.push_str(&section("project"));
+        match self.mode {
+            VersioningMode::Lockstep => out.push_str(&format!(
+                "# All {} members are at version {}.\n",
+                self.members.len(),
+                self.members[0].1
+            )),
+            VersioningMode::Independent if self.members.len() > 1 => {
+                out.push_str("# Members are at different versions.\n")
+            }
+            VersioningMode::Independent => {}
+        }
+        out.push_str(
+            "# Informational only: bumps do not enforce it; `cargo goosectl sync` aligns versions.\n",
+        );
+        out.push_str(&format!("versioning = \"{}\"\n", self.mode_name()));
+        out.push_str(
+            "# Uncomment to refuse bumps that leave dependents with unsatisfied requirements.\n",
+        );
+        out.push_str("# broken_dependents = \"fail\"\n\n");
+
+        out.push_str("# Uncomment to only allow these prerelease identifiers, earliest first.\n");
+        out.push_str(&format!("# {}", section("project.prerelease")));
+        out.push_str("# identifiers = [\"alpha\", \"beta\", \"rc\"]\n");
+        out.push_str(
+            "# Spell prereleases differently from `<identifier>.<counter>`, e.g. `rc1`:\n",
+        );
+        out.push_str("# pattern = '(?<ident>[a-z]+)(?<counter>\\d+)'\n");
+        out.push_str("# template = \"{ident}{counter}\"\n\n");
+
+        out.push_str("# Workspace members:\n");
+        for (name, version, deps) in &self.members {
+            match deps.is_empty() {
+                true => out.push_str(&format!("#   {name} {version}\n")),
+                false => out.push_str(&format!(
+                    "#   {name} {version} (path dependencies: {})\n",
+                    deps.iter().cloned().collect::<Vec<_>>().join(", ")
+                )),
+            }
+        }
+        out.push_str(&section("rust"));
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{Config, validate},
+        testing::Workspace,
+    };
+
+    fn workspace(core: &str, app: &str) -> Workspace {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\", \"app\"]\n");
+        workspace
+            .package(
+                "core",
+                &format!("[package]\nname = \"core\"\nversion = \"{core}\"\nedition = \"2021\"\n"),
+            )
+            .package(
+                "app",
+                &format!(
+                    "[package]\nname = \"app\"\nversion = \"{app}\"\nedition = \"2021\"\n\n\
+                     [dependencies]\ncore = {{ path = \"../core\" }}\n"
+                ),
+            );
+        workspace
+    }
+
+    fn survey(workspace: &Workspace) -> Survey {
+        Survey::new(&workspace.metadata().workspace_members())
+    }
+
+    #[test]
+    fn detects_lockstep() {
+        let workspace = workspace("1.2.3", "1.2.3");
+
+        assert_eq!(survey(&workspace).mode, VersioningMode::Lockstep);
+    }
+
+    #[test]
+    fn detects_independent() {
+        let workspace = workspace("1.2.3", "0.4.0");
+
+        assert_eq!(survey(&workspace).mode, VersioningMode::Independent);
+    }
+
+    #[test]
+    fn records_path_dependencies() {
+        let workspace = workspace("1.2.3", "1.2.3");
+
+        let survey = survey(&workspace);
+        let app = survey.members.iter().find(|(n, _, _)| n == "app").unwrap();
+
+        assert!(app.2.contains("core"));
+    }
+
+    #[test]
+    fn rendered_file_is_valid() {
+        let workspace = workspace("1.2.3", "1.2.3");
+
+        let rendered = survey(&workspace).render(None);
+        let value: serde_json::Value = toml_edit::de::from_str(&rendered).unwrap();
+
+        assert!(validate(&value).is_empty());
+        let config: Config = toml_edit::de::from_str(&rendered).unwrap();
+        assert_eq!(config.project().versioning(), VersioningMode::Lockstep);
+        assert!(rendered.contains("#   app 1.2.3 (path dependencies: core)"));
+    }
+
+    #[test]
+    fn manifest_gets_workspace_table() {
+        let workspace = workspace("1.2.3", "0.4.0");
+        let args = InitArgs {
+            manifest: true,
+            force: false,
+        };
+
+        let out = args
+            .embed_in_manifest("[workspace]\nmembers = []\n", &survey(&workspace))
+            .unwrap();
+        let doc = out.parse::<toml_edit::DocumentMut>().unwrap();
+
+        assert_eq!(
+            doc["workspace"]["metadata"]["goosectl"]["project"]["versioning"].as_str(),
+            Some("independent")
+        );
+    }
+
+    #[test]
+    fn manifest_refuses_to_overwrite() {
+        let workspace = workspace("1.2.3", "1.2.3");
+        let manifest = "[workspace]\nmembers = []\n\n[workspace.metadata.goosectl]\nversion = 1\n";
+        let args = InitArgs {
+            manifest: true,
+            force: false,
+        };
+
+        assert!(
+            args.embed_in_manifest(manifest, &survey(&workspace))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn manifest_force_replaces_table() {
+        let workspace = workspace("1.2.3", "1.2.3");
+        let manifest = "[workspace]\nmembers = []\n\n[workspace.metadata.goosectl]\nversion = 1\n";
+        let args = InitArgs {
+            manifest: true,
+            force: true,
+        };
+
+        let out = args
+            .embed_in_manifest(manifest, &survey(&workspace))
+            .unwrap();
+
+        assert_eq!(out.matches("[workspace.metadata.goosectl]").count(), 1);
+    }
+}
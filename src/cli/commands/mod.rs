@@ -1,5 +1,13 @@
 mod bump;
+mod config;
 mod current_version;
+mod init;
+mod normalize;
+mod sync;
 
-pub use bump::BumpArgs;
+pub use bump::{BumpArgs, BumpReport};
+pub use config::ConfigArgs;
 pub use current_version::CurrentVersionArgs;
+pub use init::InitArgs;
+pub use normalize::NormalizeArgs;
+pub use sync::SyncArgs;
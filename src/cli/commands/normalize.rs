@@ -0,0 +1,296 @@
+use std::{
+    io::{BufRead, IsTerminal, Write},
+    path::Path,
+};
+
+use anyhow::{Result, bail};
+use cargo_metadata::{Package, semver::Version};
+use clap::Args;
+use toml_edit::TableLike;
+
+use crate::{
+    bump::plan::StagedManifests,
+    cli::global_args::GlobalArgs,
+    config::WorkspaceConfig,
+    manifest,
+    version::{legacy::normalize, semantic_version::SemanticVersion},
+};
+
+#[derive(Debug, Clone, Args)]
+pub struct NormalizeArgs {
+    #[arg(short, long, help = "Apply the plan without asking for confirmation")]
+    yes: bool,
+}
+
+impl NormalizeArgs {
+    pub fn execute(
+        &self,
+        metadata: &crate::metadata::Metadata,
+        config: &WorkspaceConfig,
+        global: &GlobalArgs,
+    ) -> Result<()> {
+        // every member unless packages were picked explicitly
+        let packages = match global.workspace || global.package.is_empty() {
+            true => metadata.workspace_members(),
+            false => metadata.select_packages(false, &global.package)?,
+        };
+
+        let mut candidates: Vec<(&Package, SemanticVersion)> = Vec::new();
+        let mut unsupported = Vec::new();
+
+        for package in packages {
+            if config.package_version(package).is_ok() {
+                continue;
+            }
+
+            match normalize(&package.version, config.prerelease_scheme(&package.name)) {
+                Ok(next) => candidates.push((package, next)),
+                Err(e) => unsupported.push(format!("{} {}: {}", package.name, package.version, e)),
+            }
+        }
+
+        // stage every edit in memory so nothing is written unless the plan is accepted
+        let mut staged = StagedManifests::default();
+        let mut plan = Vec::new();
+        let mut renamed: Vec<(&Package, SemanticVersion)> = Vec::new();
+
+        let root_manifest = metadata.workspace_root().join("Cargo.toml");
+
+        for (package, next) in candidates {
+            let doc = staged.stage(package.manifest_path.as_std_path())?;
+
+            if manifest::inherits_version(doc) {
+                // rewrite `[workspace.package]` once for all members sharing it
+                let root = staged.stage(&root_manifest)?;
+                let Some(version) = manifest::workspace_version_mut(root) else {
+                    unsupported.push(format!(
+                        "{} {}: no `[workspace.package]` version found in {}",
+                        package.name,
+                        package.version,
+                        root_manifest.display()
+                    ));
+                    continue;
+                };
+
+                if version.as_str() == Some(package.version.to_string().as_str()) {
+                    manifest::set_str(version, next.to_string());
+                }
+
+                plan.push(format!(
+                    "{}: {} -> {} (in [workspace.package])",
+                    package.name, package.version, next
+                ));
+                renamed.push((package, next));
+                continue;
+            }
+
+            let version = &mut doc["package"]["version"];
+            if !version.is_str() {
+                unsupported.push(format!(
+                    "{} {}: version is not set directly in {}",
+                    package.name, package.version, package.manifest_path
+                ));
+                continue;
+            }
+
+            manifest::set_str(version, next.to_string());
+            plan.push(format!("{}: {} -> {}", package.name, package.version, next));
+            renamed.push((package, next));
+        }
+
+        for member in metadata.workspace_members() {
+            let manifest = member.manifest_path.as_std_path();
+            let Some(manifest_dir) = manifest.parent() else {
+                continue;
+            };
+            let mut edits = Vec::new();
+
+            let doc = staged.stage(manifest)?;
+            for (_, deps) in manifest::dependency_tables_mut(doc) {
+                for (key, item) in deps.iter_mut() {
+                    let Some(dep) = item.as_table_like_mut() else {
+                        continue;
+                    };
+
+                    if let Some((req, new_req)) =
+                        rewrite_dependency(key.get(), dep, manifest_dir, &renamed)
+                    {
+                        edits.push(format!(
+                            "dependency {} in {}: \"{}\" -> \"{}\"",
+                            key.get(),
+                            member.name,
+                            req,
+                            new_req
+                        ));
+                    }
+                }
+            }
+
+            plan.extend(edits);
+        }
+
+        // members inheriting a dependency pick up the pin from the workspace
+        let root = staged.stage(&root_manifest)?;
+        if let Some(deps) = root
+            .get_mut("workspace")
+            .and_then(|workspace| workspace.get_mut("dependencies"))
+            .and_then(|deps| deps.as_table_like_mut())
+        {
+            for (key, item) in deps.iter_mut() {
+                let Some(dep) = item.as_table_like_mut() else {
+                    continue;
+                };
+
+                if let Some((req, new_req)) =
+                    rewrite_dependency(key.get(), dep, metadata.workspace_root(), &renamed)
+                {
+                    plan.push(format!(
+                        "dependency {} in [workspace.dependencies]: \"{}\" -> \"{}\"",
+                        key.get(),
+                        req,
+                        new_req
+                    ));
+                }
+            }
+        }
+
+        let lockfile = metadata.workspace_root().join("Cargo.lock");
+        if lockfile.exists() {
+            let lock = staged.stage(&lockfile)?;
+            for (package, next) in &renamed {
+                let (from, to) = (package.version.to_string(), next.to_string());
+                if manifest::set_locked_version(lock, &package.name, &from, &to).is_some() {
+                    plan.push(format!("Cargo.lock: {} {} -> {}", package.name, from, to));
+                }
+            }
+        }
+
+        if !unsupported.is_empty() {
+            println!("Cannot normalize:");
+            for line in &unsupported {
+                println!("  {line}");
+            }
+        }
+
+        if plan.is_empty() {
+            println!("No versions to normalize.");
+            return Ok(());
+        }
+
+        println!("Normalize plan:");
+        for line in &plan {
+            println!("  {line}");
+        }
+
+        if global.dry_run {
+            return Ok(());
+        }
+
+        if !self.yes && !confirm("Apply these changes?")? {
+            println!("Aborted.");
+            return Ok(());
+        }
+
+        for path in staged.write(metadata.workspace_root(), |_| Ok(()))? {
+            println!("Updated {}", path.display());
+        }
+
+        Ok(())
+    }
+}
+
+/// Rewrite the requirement of a dependency on a renamed member, returning the
+/// old and new requirement.
+fn rewrite_dependency(
+    key: &str,
+    dep: &mut dyn TableLike,
+    manifest_dir: &Path,
+    renamed: &[(&Package, SemanticVersion)],
+) -> Option<(String, String)> {
+    let name = manifest::dependency_package(key, dep);
+
+    let (package, next) = renamed.iter().find(|(p, _)| {
+        p.name == name
+            && p.manifest_path
+                .parent()
+                .is_some_and(|dir| manifest::path_points_at(manifest_dir, dep, dir.as_std_path()))
+    })?;
+
+    let req = dep.get("version")?.as_str()?.to_string();
+    let new_req = rewrite_requirement(&req, &package.version, next)?;
+    manifest::set_str(dep.get_mut("version")?, new_req.clone());
+
+    Some((req, new_req))
+}
+
+/// Swap `old` for `new` in a requirement that pins exactly `old`, keeping the
+/// operator (`=1.2.3-beta` → `=1.2.3-beta.1`).
+fn rewrite_requirement(req: &str, old: &Version, new: &SemanticVersion) -> Option<String> {
+    let trimmed = req.trim();
+    let (op, rest) = trimmed.split_at(trimmed.find(|c: char| c.is_ascii_digit())?);
+
+    let mut old = old.clone();
+    old.build = cargo_metadata::semver::BuildMetadata::EMPTY;
+    if rest != old.to_string() {
+        return None;
+    }
+
+    let new = new.clone().with_build(None).ok()?;
+    Some(format!("{op}{new}"))
+}
+
+fn confirm(prompt: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        bail!("refusing to apply changes without a terminal; pass --yes to confirm");
+    }
+
+    print!("{prompt} [y/N] ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::scheme::STRICT;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    fn sv(s: &str) -> SemanticVersion {
+        SemanticVersion::parse_with(v(s), STRICT.clone()).unwrap()
+    }
+
+    #[test]
+    fn rewrite_keeps_operator() {
+        let req = rewrite_requirement("=1.2.3-beta", &v("1.2.3-beta"), &sv("1.2.3-beta.1"));
+
+        assert_eq!(req.as_deref(), Some("=1.2.3-beta.1"));
+    }
+
+    #[test]
+    fn rewrite_bare_requirement() {
+        let req = rewrite_requirement("1.2.3-rc1", &v("1.2.3-rc1"), &sv("1.2.3-rc.1"));
+
+        assert_eq!(req.as_deref(), Some("1.2.3-rc.1"));
+    }
+
+    #[test]
+    fn rewrite_drops_build_metadata() {
+        let req = rewrite_requirement("^1.2.3-rc1", &v("1.2.3-rc1+abc"), &sv("1.2.3-rc.1+abc"));
+
+        assert_eq!(req.as_deref(), Some("^1.2.3-rc.1"));
+    }
+
+    #[test]
+    fn rewrite_ignores_other_requirements() {
+        let req = rewrite_requirement("^1.2", &v("1.2.3-rc1"), &sv("1.2.3-rc.1"));
+
+        assert_eq!(req, None);
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Result, bail};
+use cargo_metadata::semver::Version;
+use clap::Args;
+
+use super::bump::print_plan;
+use crate::{
+    bump::{
+        cascade::change_level,
+        plan::{BumpOptions, BumpPlan, LockfileUpdate, Propagation},
+    },
+    cli::global_args::GlobalArgs,
+    config::WorkspaceConfig,
+    version::{
+        semantic_version::{ReleaseLevel, SemanticVersion},
+        transition::TransitionInput,
+    },
+};
+
+#[derive(Debug, Clone, Args)]
+pub struct SyncArgs {
+    #[arg(help = "The version to move every member to [default: the highest current version]")]
+    version: Option<Version>,
+    #[arg(long, help = "Allow moving members to a lower version")]
+    allow_downgrade: bool,
+    #[arg(
+        long,
+        value_name = "POLICY",
+        default_value = "always",
+        help = "When to update workspace dependency requirements on synced packages"
+    )]
+    propagate: Propagation,
+    #[arg(
+        long,
+        value_name = "MODE",
+        default_value = "edit",
+        help = "How to update Cargo.lock for synced packages"
+    )]
+    lockfile: LockfileUpdate,
+}
+
+impl SyncArgs {
+    pub fn execute(
+        &self,
+        metadata: &crate::metadata::Metadata,
+        config: &WorkspaceConfig,
+        global: &GlobalArgs,
+    ) -> Result<()> {
+        // every member unless packages were picked explicitly
+        let packages = match global.workspace || global.package.is_empty() {
+            true => metadata.workspace_members(),
+            false => metadata.select_packages(false, &global.package)?,
+        };
+
+        let current = packages
+            .iter()
+            .map(|package| Ok((*package, config.package_version(package)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let target = match &self.version {
+            Some(version) => version.clone(),
+            None => {
+                let mut versions = current.iter().map(|(_, version)| version);
+                let Some(mut highest) = versions.next() else {
+                    bail!("No packages found.");
+                };
+                for version in versions {
+                    if highest.precedes(version)? {
+                        highest = version;
+                    }
+                }
+                highest.version().clone()
+            }
+        };
+
+        // members already there are left out of the plan
+        let selection: Vec<_> = current
+            .iter()
+            .filter(|(_, version)| *version.version() != target)
+            .map(|(package, _)| {
+                let transition = TransitionInput::Set {
+                    version: target.clone(),
+                    allow_downgrade: self.allow_downgrade,
+                };
+                (*package, transition)
+            })
+            .collect();
+
+        let plan = BumpPlan::new(
+            metadata,
+            config,
+            &selection,
+            &BumpOptions {
+                propagation: Some(self.propagate),
+                cascade: false,
+                lockfile: self.lockfile,
+            },
+        )?;
+
+        if !global.dry_run {
+            plan.apply()?;
+        }
+
+        print_plan(&plan, global);
+
+        let prefix = if global.dry_run { "[DRY RUN] " } else { "" };
+
+        println!();
+        println!("{}Synced {} members to {}:", prefix, current.len(), target);
+        for (package, version) in &current {
+            let target = SemanticVersion::parse_with(
+                target.clone(),
+                config.prerelease_scheme(&package.name),
+            )?;
+            println!("  {}: {}", package.name, movement(version, &target)?);
+        }
+
+        Ok(())
+    }
+}
+
+/// How far a member moves to reach `target`, e.g. `1.2.0 -> 2.0.0 (major)`.
+fn movement(from: &SemanticVersion, target: &SemanticVersion) -> Result<String> {
+    if from.version() == target.version() {
+        return Ok(format!("{from} (unchanged)"));
+    }
+
+    let same_release = (from.major(), from.minor(), from.patch())
+        == (target.major(), target.minor(), target.patch());
+
+    let change = if target.precedes(from)? {
+        "downgrade"
+    } else if same_release && from.version().pre == target.version().pre {
+        "build metadata"
+    } else if same_release {
+        "prerelease"
+    } else {
+        match change_level(from.version(), target.version()) {
+            ReleaseLevel::Patch => "patch",
+            ReleaseLevel::Minor => "minor",
+            ReleaseLevel::Major => "major",
+        }
+    };
+
+    Ok(format!("{from} -> {target} ({change})"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> SemanticVersion {
+        SemanticVersion::parse_with(
+            Version::parse(s).unwrap(),
+            crate::version::scheme::STRICT.clone(),
+        )
+        .unwrap()
+    }
+
+    fn movement_to(from: &str, to: &str) -> String {
+        movement(&sv(from), &sv(to)).unwrap()
+    }
+
+    #[test]
+    fn describes_movement() {
+        assert_eq!(movement_to("1.2.0", "1.2.0"), "1.2.0 (unchanged)");
+        assert_eq!(movement_to("1.2.0", "2.0.0"), "1.2.0 -> 2.0.0 (major)");
+        assert_eq!(movement_to("1.2.0", "1.2.1"), "1.2.0 -> 1.2.1 (patch)");
+        assert_eq!(
+            movement_to("2.0.0-rc.1", "2.0.0"),
+            "2.0.0-rc.1 -> 2.0.0 (prerelease)"
+        );
+        assert_eq!(movement_to("1.3.0", "1.2.0"), "1.3.0 -> 1.2.0 (downgrade)");
+    }
+
+    #[test]
+    fn orders_prereleases_by_scheme() {
+        let scheme = std::sync::Arc::new(
+            crate::version::scheme::PrereleaseScheme::default()
+                .with_identifiers(vec!["dev".into(), "alpha".into()])
+                .unwrap(),
+        );
+        let ordered = |s: &str| {
+            SemanticVersion::parse_with(Version::parse(s).unwrap(), scheme.clone()).unwrap()
+        };
+
+        assert_eq!(
+            movement(&ordered("1.0.0-dev.1"), &ordered("1.0.0-alpha.1")).unwrap(),
+            "1.0.0-dev.1 -> 1.0.0-alpha.1 (prerelease)"
+        );
+        assert_eq!(
+            movement(&ordered("1.0.0-alpha.1"), &ordered("1.0.0-dev.1")).unwrap(),
+            "1.0.0-alpha.1 -> 1.0.0-dev.1 (downgrade)"
+        );
+    }
+}
@@ -1,12 +1,28 @@
+use std::path::PathBuf;
+
 use clap::Args;
 
 #[derive(Debug, Args)]
 pub struct GlobalArgs {
     #[arg(long, help = "Do not write changes to disk")]
     pub dry_run: bool,
+    #[arg(short, long, help = "Also report what was left unchanged and why")]
+    pub verbose: bool,
 
     #[arg(long, help = "Apply to the whole workspace")]
     pub workspace: bool,
-    #[arg(short, long, value_name = "NAME", help = "Package to modify")]
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Package to modify (`bump` also takes `NAME=BUMP`, e.g. `core=minor:rc`)"
+    )]
     pub package: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Path to a goosectl.toml file (defaults to the closest one up to the workspace root)"
+    )]
+    pub config: Option<PathBuf>,
 }
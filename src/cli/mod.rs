@@ -1,12 +1,19 @@
+use std::path::Path;
+
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use std::collections::HashMap;
+use schemars::{Schema, schema_for};
 
-use crate::version::semantic_version::SemanticVersion;
+use crate::config::WorkspaceConfig;
 
 mod commands;
 mod global_args;
 
+/// JSON schema for the report printed by `bump --format json`.
+pub fn bump_report_schema() -> Schema {
+    schema_for!(commands::BumpReport)
+}
+
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(name = "cargo-goosectl")]
@@ -25,120 +32,620 @@ pub struct Cli {
 }
 
 impl Cli {
-    pub fn execute(&self, metadata: &crate::metadata::Metadata) -> Result<()> {
-        self.cmd.execute(metadata, &self.global)
+    pub fn execute(&self, metadata: &crate::metadata::Metadata, cwd: &Path) -> Result<()> {
+        self.cmd.execute(metadata, cwd, &self.global)
     }
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
     Bump(commands::BumpArgs),
+    Config(commands::ConfigArgs),
     CurrentVersion(commands::CurrentVersionArgs),
+    Init(commands::InitArgs),
+    Normalize(commands::NormalizeArgs),
+    Sync(commands::SyncArgs),
 }
 
 impl Command {
     pub fn execute(
         &self,
         metadata: &crate::metadata::Metadata,
+        cwd: &Path,
         global: &global_args::GlobalArgs,
     ) -> Result<()> {
+        // loaded only by the commands that need it, so that `init` and
+        // `config validate` still work on an invalid configuration
+        let load = || -> Result<WorkspaceConfig> {
+            // merge Cargo.toml metadata, goosectl.toml (or --config) and GOOSECTL_* overrides
+            let config = WorkspaceConfig::load(global.config.as_deref(), cwd, metadata)?;
+            for warning in config.warnings() {
+                eprintln!("warning: {warning}");
+            }
+            Ok(config)
+        };
+
         match self {
-            Command::Bump(args) => self.bump(metadata, args, global),
-            Command::CurrentVersion(args) => args.execute(metadata, global),
+            Command::Bump(args) => args.execute(metadata, &load()?, global),
+            Command::Config(args) => args.execute(metadata, cwd, load, global),
+            Command::CurrentVersion(args) => args.execute(metadata, &load()?, global),
+            Command::Init(args) => args.execute(metadata, global),
+            Command::Normalize(args) => args.execute(metadata, &load()?, global),
+            Command::Sync(args) => args.execute(metadata, &load()?, global),
         }
     }
+}
 
-    fn bump(
-        &self,
-        metadata: &crate::metadata::Metadata,
-        args: &commands::BumpArgs,
-        global: &global_args::GlobalArgs,
-    ) -> Result<()> {
-        // Determine which packages are being directly bumped
-        let packages = metadata.select_packages(global.workspace, global.package.as_slice())?;
-
-        // Map of package name -> new version, used later for propagation
-        let mut updated_packages = HashMap::new();
-
-        let prefix = if global.dry_run { "[DRY RUN] " } else { "" };
-
-        // Phase 1: apply the version transition to selected packages
-        for package in &packages {
-            let curr: SemanticVersion = package.version.clone().try_into()?;
-            let transition = args.clone().into();
-            let next = curr.apply(transition)?;
-
-            // Write the new package version to Cargo.toml
-            if !global.dry_run {
-                let contents = std::fs::read_to_string(&package.manifest_path)?;
-                let mut doc = contents.parse::<toml_edit::DocumentMut>()?;
-                doc["package"]["version"] = next.to_string().into();
-                std::fs::write(&package.manifest_path, doc.to_string())?;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Workspace;
+
+    const ROOT: &str = r#"[workspace]
+members = ["core", "app", "tool"]
+resolver = "2"
+
+[workspace.package]
+version = "1.0.0" # shared
+edition = "2021"
+"#;
 
-            println!(
-                "{}Updated package {} from version {} to {}",
-                prefix, package.name, curr, next
+    fn workspace() -> Workspace {
+        let workspace = Workspace::new(ROOT);
+        workspace
+            .package(
+                "core",
+                "[package]\nname = \"core\"\nversion.workspace = true\nedition.workspace = true\n",
+            )
+            .package(
+                "app",
+                "[package]\nname = \"app\"\nversion.workspace = true\nedition.workspace = true\n",
+            )
+            .package(
+                "tool",
+                "[package]\nname = \"tool\"\nversion = \"0.3.0\"\nedition = \"2021\"\n",
             );
+        workspace
+    }
 
-            // Record updated versions for dependency propagation
-            updated_packages.insert(package.name.to_string(), next.clone());
-        }
+    fn run(workspace: &Workspace, args: &[&str]) -> Result<()> {
+        let cli = Cli::try_parse_from(std::iter::once("goosectl").chain(args.iter().copied()))?;
+        cli.execute(&workspace.metadata(), workspace.root())
+    }
+
+    fn read(workspace: &Workspace, relative: &str) -> String {
+        std::fs::read_to_string(workspace.path(relative)).unwrap()
+    }
+
+    /// Manifests for `app` declaring a path dependency on `core` in the
+    /// various ways Cargo accepts.
+    const LAYOUTS: &[(&str, &str)] = &[
+        (
+            "table",
+            include_str!("../../tests/fixtures/dependencies/table.toml"),
+        ),
+        (
+            "inline",
+            include_str!("../../tests/fixtures/dependencies/inline.toml"),
+        ),
+        (
+            "dotted",
+            include_str!("../../tests/fixtures/dependencies/dotted.toml"),
+        ),
+        (
+            "dev_inline",
+            include_str!("../../tests/fixtures/dependencies/dev_inline.toml"),
+        ),
+        (
+            "dev_exact",
+            include_str!("../../tests/fixtures/dependencies/dev_exact.toml"),
+        ),
+        (
+            "build_dotted",
+            include_str!("../../tests/fixtures/dependencies/build_dotted.toml"),
+        ),
+        (
+            "target_inline",
+            include_str!("../../tests/fixtures/dependencies/target_inline.toml"),
+        ),
+        (
+            "target_table",
+            include_str!("../../tests/fixtures/dependencies/target_table.toml"),
+        ),
+    ];
+
+    #[test]
+    fn propagates_into_every_layout() {
+        for (name, app) in LAYOUTS {
+            let workspace =
+                Workspace::new("[workspace]\nmembers = [\"core\", \"app\"]\nresolver = \"2\"\n");
+            workspace
+                .package(
+                    "core",
+                    "[package]\nname = \"core\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+                )
+                .package("app", app);
 
-        // Propagation is enabled only in workspace mode (explicit or implicit)
-        // and can be disabled explicitly via --no-propagate
-        let propagate = !args.no_propagate && (global.workspace || packages.len() > 1);
+            run(&workspace, &["--workspace", "bump", "version", "minor"]).unwrap();
 
-        if !propagate {
-            return Ok(());
+            let expected = app.replace("0.1.0", "0.2.0").replace("5.0.0", "5.1.0");
+            assert_eq!(
+                read(&workspace, "app/Cargo.toml"),
+                expected,
+                "layout `{name}`"
+            );
         }
+    }
 
-        // Phase 2: propagate updated versions to all workspace dependents
-        for package in metadata.all_packages()? {
-            let contents = std::fs::read_to_string(&package.manifest_path)?;
-            let mut doc = contents.parse::<toml_edit::DocumentMut>()?;
-            let mut changed = false;
-
-            // Inspect all dependency sections that Cargo understands
-            for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
-                let Some(deps) = doc.get_mut(section).and_then(|v| v.as_table_mut()) else {
-                    continue;
-                };
-
-                for (dep_name, dep_item) in deps.iter_mut() {
-                    let dep_name_str = dep_name.get();
-
-                    // Only consider dependencies whose package was bumped
-                    let Some(new_version) = updated_packages.get(dep_name_str) else {
-                        continue;
-                    };
-
-                    // Only rewrite workspace/path dependencies to avoid touching registry deps
-                    let is_path_dep = dep_item.as_table().and_then(|t| t.get("path")).is_some();
-
-                    if !is_path_dep {
-                        continue;
-                    }
-
-                    // Mutate only the version field, preserving path, features, etc.
-                    if let Some(table) = dep_item.as_table_mut() {
-                        table["version"] = toml_edit::value(new_version.to_string());
-                        changed = true;
-                    }
-
-                    println!(
-                        "{}Updated dependency {} in package {} to {}",
-                        prefix, dep_name, package.name, new_version
-                    );
-                }
-            }
+    fn requirement_workspace(root_extra: &str, app_dependency: &str) -> Workspace {
+        let workspace = Workspace::new(&format!(
+            "[workspace]\nmembers = [\"core\", \"app\"]\nresolver = \"2\"\n{root_extra}"
+        ));
+        workspace
+            .package(
+                "core",
+                "[package]\nname = \"core\"\nversion = \"1.2.0\"\nedition = \"2021\"\n",
+            )
+            .package(
+                "app",
+                &format!(
+                    "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n{app_dependency}\n"
+                ),
+            );
+        workspace
+    }
 
-            // Write back the manifest only if something actually changed
-            if changed && !global.dry_run {
-                std::fs::write(&package.manifest_path, doc.to_string())?;
-            }
+    #[test]
+    fn preserves_requirement_precision() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "~1.2" }"#);
+
+        run(&workspace, &["--workspace", "bump", "version", "minor"]).unwrap();
+
+        assert!(read(&workspace, "app/Cargo.toml").contains(r#"version = "~1.3" }"#));
+    }
+
+    #[test]
+    fn leaves_versionless_path_dependency_alone() {
+        let workspace = requirement_workspace("", r#"core = { path = "../core" }"#);
+
+        run(&workspace, &["--workspace", "bump", "version", "minor"]).unwrap();
+
+        assert!(read(&workspace, "app/Cargo.toml").contains(r#"core = { path = "../core" }"#));
+    }
+
+    #[test]
+    fn configured_requirement_style_normalises() {
+        let workspace = requirement_workspace(
+            "\n[workspace.metadata.goosectl.rust]\nrequirement_style = \"exact\"\n",
+            r#"core = { path = "../core" }"#,
+        );
+
+        run(&workspace, &["--workspace", "bump", "version", "minor"]).unwrap();
+
+        let app = read(&workspace, "app/Cargo.toml");
+        assert!(
+            app.contains(r#"core = { path = "../core", version = "=1.3.0" }"#),
+            "{app}"
+        );
+    }
+
+    #[test]
+    fn when_needed_keeps_satisfied_requirements() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "^1.2" }"#);
+
+        run(
+            &workspace,
+            &[
+                "--workspace",
+                "bump",
+                "--propagate",
+                "when-needed",
+                "version",
+                "patch",
+            ],
+        )
+        .unwrap();
+
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.2.1""#));
+        assert!(read(&workspace, "app/Cargo.toml").contains(r#"version = "^1.2" }"#));
+    }
+
+    #[test]
+    fn when_needed_rewrites_broken_requirements() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "^1.2" }"#);
+
+        run(
+            &workspace,
+            &[
+                "--workspace",
+                "bump",
+                "--propagate",
+                "when-needed",
+                "version",
+                "major",
+            ],
+        )
+        .unwrap();
+
+        assert!(read(&workspace, "app/Cargo.toml").contains(r#"version = "^2.0" }"#));
+    }
+
+    #[test]
+    fn explicit_policy_applies_to_single_package() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "1.2.0" }"#);
+
+        run(&workspace, &["-p", "core", "bump", "version", "minor"]).unwrap();
+        assert!(read(&workspace, "app/Cargo.toml").contains(r#"version = "1.2.0" }"#));
+
+        run(
+            &workspace,
+            &[
+                "-p",
+                "core",
+                "bump",
+                "--propagate",
+                "always",
+                "version",
+                "minor",
+            ],
+        )
+        .unwrap();
+        assert!(read(&workspace, "app/Cargo.toml").contains(r#"version = "1.4.0" }"#));
+    }
+
+    #[test]
+    fn broken_dependents_warn_by_default() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "^1.2" }"#);
+
+        run(&workspace, &["-p", "core", "bump", "version", "major"]).unwrap();
+
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "2.0.0""#));
+        assert!(read(&workspace, "app/Cargo.toml").contains(r#"version = "^1.2" }"#));
+    }
+
+    #[test]
+    fn broken_dependents_can_fail() {
+        let workspace = requirement_workspace(
+            "\n[workspace.metadata.goosectl.project]\nbroken_dependents = \"fail\"\n",
+            r#"core = { path = "../core", version = "^1.2" }"#,
+        );
+
+        let err = run(&workspace, &["-p", "core", "bump", "version", "major"]).unwrap_err();
+
+        assert!(err.to_string().contains("app requires core ^1.2"), "{err}");
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.2.0""#));
+
+        // compatible bumps are fine
+        run(&workspace, &["-p", "core", "bump", "version", "minor"]).unwrap();
+    }
+
+    #[test]
+    fn cascades_into_dependents() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "1.2.0" }"#);
+
+        run(
+            &workspace,
+            &["-p", "core", "bump", "--cascade", "version", "major"],
+        )
+        .unwrap();
+
+        let app = read(&workspace, "app/Cargo.toml");
+        assert!(app.contains("version = \"1.0.0\"\n"), "{app}");
+        assert!(app.contains(r#"version = "2.0.0" }"#), "{app}");
+    }
+
+    #[test]
+    fn propagates_through_renames_only_to_members() {
+        let workspace = Workspace::new(
+            "[workspace]\nmembers = [\"core\", \"app\"]\nexclude = [\"vendor\"]\nresolver = \"2\"\n",
+        );
+        workspace
+            .package(
+                "core",
+                "[package]\nname = \"my-core\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            )
+            .package(
+                "vendor/app",
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            )
+            .package(
+                "app",
+                r#"[package]
+name = "app"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+core = { package = "my-core", path = "../core", version = "0.1.0" }
+
+[dev-dependencies]
+app-fork = { package = "app", path = "../vendor/app", version = "0.1.0" }
+"#,
+            );
+
+        run(&workspace, &["--workspace", "bump", "version", "minor"]).unwrap();
+
+        let app = read(&workspace, "app/Cargo.toml");
+        assert!(
+            app.contains(r#"core = { package = "my-core", path = "../core", version = "0.2.0" }"#),
+            "{app}"
+        );
+        assert!(
+            app.contains(
+                r#"app-fork = { package = "app", path = "../vendor/app", version = "0.1.0" }"#
+            ),
+            "{app}"
+        );
+    }
+
+    #[test]
+    fn propagates_into_workspace_dependencies() {
+        let workspace = Workspace::new(
+            r#"[workspace]
+members = ["core", "app"]
+resolver = "2"
+
+[workspace.dependencies]
+core = { path = "core", version = "0.1.0" }
+serde = "1"
+"#,
+        );
+        workspace
+            .package(
+                "core",
+                "[package]\nname = \"core\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            )
+            .package(
+                "app",
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\ncore.workspace = true\n",
+            );
+
+        run(&workspace, &["--workspace", "bump", "version", "minor"]).unwrap();
+
+        let root = read(&workspace, "Cargo.toml");
+        assert!(
+            root.contains(r#"core = { path = "core", version = "0.2.0" }"#),
+            "{root}"
+        );
+        assert!(root.contains(r#"serde = "1""#));
+        assert!(read(&workspace, "app/Cargo.toml").contains("core.workspace = true"));
+    }
+
+    #[test]
+    fn bumps_inherited_version_once() {
+        let workspace = workspace();
+
+        run(&workspace, &["--workspace", "bump", "version", "minor"]).unwrap();
+
+        assert!(read(&workspace, "Cargo.toml").contains("version = \"1.1.0\" # shared"));
+        assert!(read(&workspace, "core/Cargo.toml").contains("version.workspace = true"));
+        assert!(read(&workspace, "app/Cargo.toml").contains("version.workspace = true"));
+        assert!(read(&workspace, "tool/Cargo.toml").contains("version = \"0.4.0\""));
+    }
+
+    #[test]
+    fn rejects_partial_inherited_selection() {
+        let workspace = workspace();
+
+        let err = run(&workspace, &["-p", "core", "bump", "version", "minor"]).unwrap_err();
+
+        assert!(err.to_string().contains("`app`"), "{err}");
+        assert!(read(&workspace, "Cargo.toml").contains("version = \"1.0.0\""));
+    }
+
+    #[test]
+    fn bumps_inherited_version_for_full_selection() {
+        let workspace = workspace();
+
+        run(
+            &workspace,
+            &["-p", "core", "-p", "app", "bump", "version", "patch"],
+        )
+        .unwrap();
+
+        assert!(read(&workspace, "Cargo.toml").contains("version = \"1.0.1\""));
+        assert!(read(&workspace, "tool/Cargo.toml").contains("version = \"0.3.0\""));
+    }
+
+    #[test]
+    fn sets_explicit_version() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "1.2.0" }"#);
+
+        run(&workspace, &["--workspace", "bump", "set", "2.0.0"]).unwrap();
+
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "2.0.0""#));
+        assert!(read(&workspace, "app/Cargo.toml").contains(r#"version = "2.0.0" }"#));
+
+        let err = run(&workspace, &["-p", "core", "bump", "set", "1.9.0"]).unwrap_err();
+        assert!(err.to_string().contains("downgrade"), "{err}");
+
+        run(
+            &workspace,
+            &["-p", "core", "bump", "set", "--allow-downgrade", "1.9.0"],
+        )
+        .unwrap();
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.9.0""#));
+    }
+
+    #[test]
+    fn bumps_each_package_its_own_way() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "1.2.0" }"#);
+
+        run(
+            &workspace,
+            &["-p", "core=minor", "-p", "app=patch:rc", "bump"],
+        )
+        .unwrap();
+
+        let app = read(&workspace, "app/Cargo.toml");
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.3.0""#));
+        assert!(app.contains(r#"version = "0.1.1-rc.1""#), "{app}");
+        assert!(app.contains(r#"version = "1.3.0" }"#), "{app}");
+
+        // packages without a spec need the subcommand
+        let err = run(&workspace, &["-p", "core=minor", "-p", "app", "bump"]).unwrap_err();
+        assert!(err.to_string().contains("no bump given for `app`"), "{err}");
+
+        run(
+            &workspace,
+            &["-p", "core=patch", "-p", "app", "bump", "prerelease"],
+        )
+        .unwrap();
+        assert!(read(&workspace, "app/Cargo.toml").contains(r#"version = "0.1.1-rc.2""#));
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.3.1""#));
+    }
+
+    #[test]
+    fn init_keeps_manifest_configuration() {
+        let workspace = requirement_workspace(
+            "\n[workspace.metadata.goosectl]\nversion = 1\n",
+            r#"core = { path = "../core" }"#,
+        );
+
+        let err = run(&workspace, &["init"]).unwrap_err();
+        assert!(
+            err.to_string().contains("[workspace.metadata.goosectl]"),
+            "{err}"
+        );
+        assert!(!workspace.path("goosectl.toml").exists());
+
+        run(&workspace, &["init", "--force"]).unwrap();
+        assert!(workspace.path("goosectl.toml").exists());
+    }
+
+    #[test]
+    fn validates_a_configuration_that_does_not_load() {
+        let workspace = workspace();
+        workspace.file("goosectl.toml", "verison = 1\n");
+        assert!(run(&workspace, &["current-version"]).is_err());
+
+        let err = run(&workspace, &["config", "validate"]).unwrap_err();
+
+        assert!(err.to_string().contains("failed validation"), "{err}");
+        run(&workspace, &["config", "migrate"]).unwrap();
+        run(&workspace, &["init", "--force"]).unwrap();
+    }
+
+    #[test]
+    fn rejects_repeated_package_specs() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "1.2.0" }"#);
+
+        let err = run(
+            &workspace,
+            &["-p", "core=minor", "-p", "core=major", "bump"],
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("more than one bump"), "{err}");
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.2.0""#));
+
+        let err = run(&workspace, &["-p", "core=minor", "-p", "core", "bump"]).unwrap_err();
+        assert!(err.to_string().contains("both with and without"), "{err}");
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.2.0""#));
+    }
+
+    #[test]
+    fn bump_requires_a_subcommand_without_specs() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "1.2.0" }"#);
+
+        for args in [&["bump"][..], &["-p", "core", "bump"]] {
+            let err = run(&workspace, args).unwrap_err();
+            let err = err.downcast_ref::<clap::Error>().expect("a usage error");
+
+            assert_eq!(err.kind(), clap::error::ErrorKind::MissingSubcommand);
+            assert!(
+                err.to_string().contains("Usage: cargo goosectl bump"),
+                "{err}"
+            );
         }
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.2.0""#));
+    }
+
+    #[test]
+    fn inherited_versions_need_the_same_bump() {
+        let workspace = workspace();
+
+        let err = run(&workspace, &["-p", "core=minor", "-p", "app=patch", "bump"]).unwrap_err();
+
+        assert!(err.to_string().contains("differently"), "{err}");
+    }
+
+    #[test]
+    fn syncs_members_to_one_version() {
+        let workspace =
+            requirement_workspace("", r#"core = { path = "../core", version = "1.2.0" }"#);
+
+        run(&workspace, &["sync"]).unwrap();
+
+        assert!(read(&workspace, "app/Cargo.toml").contains("version = \"1.2.0\"\n"));
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.2.0""#));
+
+        run(&workspace, &["sync", "2.0.0"]).unwrap();
+
+        let app = read(&workspace, "app/Cargo.toml");
+        assert!(app.contains("version = \"2.0.0\"\n"), "{app}");
+        assert!(app.contains(r#"version = "2.0.0" }"#), "{app}");
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "2.0.0""#));
+
+        let err = run(&workspace, &["sync", "1.0.0"]).unwrap_err();
+        assert!(err.to_string().contains("downgrade"), "{err}");
+    }
+
+    #[test]
+    fn normalizes_workspace_dependency_pins() {
+        let workspace = requirement_workspace(
+            "\n[workspace.dependencies]\ncore = { path = \"core\", version = \"=1.2.3-beta\" }\n",
+            "core.workspace = true",
+        );
+        workspace
+            .package(
+                "core",
+                "[package]\nname = \"core\"\nversion = \"1.2.3-beta\"\nedition = \"2021\"\n",
+            )
+            .file(
+                "Cargo.lock",
+                "version = 4\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\n \"core\",\n]\n\n[[package]]\nname = \"core\"\nversion = \"1.2.3-beta\"\n",
+            );
+
+        run(&workspace, &["normalize", "--yes"]).unwrap();
+
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.2.3-beta.1""#));
+        assert!(
+            read(&workspace, "Cargo.toml")
+                .contains(r#"core = { path = "core", version = "=1.2.3-beta.1" }"#)
+        );
+        assert!(read(&workspace, "Cargo.lock").contains(r#"version = "1.2.3-beta.1""#));
+    }
+
+    #[test]
+    fn normalize_rolls_back_on_failed_write() {
+        let workspace = requirement_workspace(
+            "\n[workspace.dependencies]\ncore = { path = \"core\", version = \"=1.2.3-beta\" }\n",
+            "core.workspace = true",
+        );
+        workspace.package(
+            "core",
+            "[package]\nname = \"core\"\nversion = \"1.2.3-beta\"\nedition = \"2021\"\n",
+        );
+        let root = read(&workspace, "Cargo.toml");
+
+        // the root manifest is written before `core`, which can no longer be replaced
+        std::fs::create_dir(workspace.path("core/.Cargo.toml.goosectl.tmp")).unwrap();
+
+        let err = run(&workspace, &["normalize", "--yes"]).unwrap_err();
 
-        Ok(())
+        assert!(err.to_string().contains("restored"), "{err:#}");
+        assert_eq!(read(&workspace, "Cargo.toml"), root);
+        assert!(read(&workspace, "core/Cargo.toml").contains(r#"version = "1.2.3-beta""#));
     }
 }
@@ -0,0 +1,55 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::version::semantic_version::ReleaseLevel;
+
+/// How dependents of a bumped package are bumped with `bump --cascade`.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct CascadeConfig {
+    /// Bump for a dependent whose dependency changed compatibly (e.g.
+    /// `1.2.0` → `1.3.0`).
+    compatible: CascadeLevel,
+
+    /// Bump for a dependent whose dependency changed in a breaking way (e.g.
+    /// `1.2.0` → `2.0.0`, or `0.2.0` → `0.3.0`).
+    breaking: CascadeLevel,
+}
+
+impl Default for CascadeConfig {
+    fn default() -> Self {
+        Self {
+            compatible: CascadeLevel::Patch,
+            breaking: CascadeLevel::Same,
+        }
+    }
+}
+
+impl CascadeConfig {
+    /// The level to bump a dependent by when one of its dependencies changed
+    /// by `change`.
+    pub fn level(&self, change: ReleaseLevel, breaking: bool) -> ReleaseLevel {
+        let level = match breaking {
+            true => self.breaking,
+            false => self.compatible,
+        };
+
+        match level {
+            CascadeLevel::Patch => ReleaseLevel::Patch,
+            CascadeLevel::Minor => ReleaseLevel::Minor,
+            CascadeLevel::Major => ReleaseLevel::Major,
+            CascadeLevel::Same => change,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CascadeLevel {
+    Patch,
+    Minor,
+    Major,
+    /// The same level the dependency was bumped by.
+    Same,
+}
@@ -1,11 +1,102 @@
+use cargo_metadata::semver::{BuildMetadata, Version};
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+use crate::version::requirement::retarget;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum Language {
     Rust,
 }
 
-#[derive(Debug, Default, Deserialize, JsonSchema)]
-pub struct LanguageConfig {}
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct LanguageConfig {
+    /// How dependency requirements on bumped members are rewritten.
+    #[serde(default)]
+    requirement_style: RequirementStyle,
+}
+
+impl LanguageConfig {
+    pub fn requirement_style(&self) -> RequirementStyle {
+        self.requirement_style
+    }
+}
+
+/// How a propagated dependency requirement is written.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RequirementStyle {
+    /// Keep each requirement's operator and precision (`~1.2` → `~1.3`).
+    /// Path dependencies without a `version` are left alone.
+    #[default]
+    Preserve,
+    /// `1.3.0`
+    Bare,
+    /// `^1.3.0`
+    Caret,
+    /// `~1.3.0`
+    Tilde,
+    /// `=1.3.0`
+    Exact,
+}
+
+impl RequirementStyle {
+    /// The requirement to write for `version` in place of `current`, or `None`
+    /// to leave the dependency as it is.
+    pub fn requirement(self, current: Option<&str>, version: &Version) -> Option<String> {
+        // build metadata has no meaning in a requirement
+        let mut version = version.clone();
+        version.build = BuildMetadata::EMPTY;
+
+        let operator = match self {
+            Self::Preserve => return retarget(current?, &version),
+            Self::Bare => "",
+            Self::Caret => "^",
+            Self::Tilde => "~",
+            Self::Exact => "=",
+        };
+
+        Some(format!("{operator}{version}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn preserve_skips_missing_requirement() {
+        assert_eq!(
+            RequirementStyle::Preserve.requirement(None, &v("1.3.0")),
+            None
+        );
+        assert_eq!(
+            RequirementStyle::Preserve
+                .requirement(Some("~1.2"), &v("1.3.0"))
+                .as_deref(),
+            Some("~1.3")
+        );
+    }
+
+    #[test]
+    fn normalising_styles_replace_requirement() {
+        assert_eq!(
+            RequirementStyle::Exact
+                .requirement(Some("^1.2"), &v("1.3.0+build.5"))
+                .as_deref(),
+            Some("=1.3.0")
+        );
+        assert_eq!(
+            RequirementStyle::Bare
+                .requirement(None, &v("1.3.0"))
+                .as_deref(),
+            Some("1.3.0")
+        );
+    }
+}
@@ -0,0 +1,585 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use cargo_metadata::Package;
+use figment::{
+    Figment, Profile, Provider,
+    providers::{Env, Serialized},
+    value::{Dict, Map},
+};
+
+use super::{
+    Config,
+    migration::{CURRENT_VERSION, migrate_value},
+};
+use crate::{
+    metadata::Metadata,
+    version::{scheme::PrereleaseScheme, semantic_version::SemanticVersion},
+};
+
+pub const CONFIG_FILE_NAME: &str = "goosectl.toml";
+pub const ENV_PREFIX: &str = "GOOSECTL_";
+
+/// Key under `[workspace.metadata]` and `[package.metadata]` holding goosectl settings.
+pub const MANIFEST_KEY: &str = "goosectl";
+
+/// Metadata name of the built-in defaults layer.
+pub(super) const DEFAULTS: &str = "default";
+
+/// Effective configuration for the workspace and each of its members.
+///
+/// Sources are merged from lowest to highest precedence:
+///
+/// 1. built-in defaults
+/// 2. `[workspace.metadata.goosectl]` in the root `Cargo.toml`
+/// 3. `goosectl.toml` (or the file passed with `--config`)
+/// 4. `[package.metadata.goosectl]` in the member's `Cargo.toml`
+/// 5. `GOOSECTL_*` environment variables
+///
+/// Sources written for an older configuration version are migrated in memory
+/// before merging, with a warning.
+pub struct WorkspaceConfig {
+    file: Option<PathBuf>,
+    workspace: Resolved,
+    packages: HashMap<String, Resolved>,
+    warnings: Vec<String>,
+}
+
+struct Resolved {
+    figment: Figment,
+    config: Config,
+    scheme: Arc<PrereleaseScheme>,
+}
+
+impl Resolved {
+    fn new(figment: Figment) -> Result<Self> {
+        // only variables for known keys, so unrelated ones like `GOOSECTL_TOKEN` are ignored
+        let keys = top_level_keys();
+        let env = Env::prefixed(ENV_PREFIX).split("__").filter(move |key| {
+            let top = key.as_str().split('.').next().unwrap_or_default();
+            keys.iter().any(|known| known.eq_ignore_ascii_case(top))
+        });
+
+        let figment = figment.merge(env);
+        let config = Config::extract(&figment)?;
+        let scheme = config
+            .project()
+            .prerelease()
+            .scheme()
+            .context("invalid goosectl configuration: `project.prerelease`")?;
+
+        Ok(Self {
+            figment,
+            config,
+            scheme: Arc::new(scheme),
+        })
+    }
+}
+
+impl WorkspaceConfig {
+    pub fn load(path: Option<&Path>, cwd: &Path, metadata: &Metadata) -> Result<Self> {
+        let mut warnings = Vec::new();
+        let mut base = Figment::new().merge(Defaults);
+
+        if let Some(table) = metadata.workspace_metadata().get(MANIFEST_KEY) {
+            base = base.merge(Source::new(
+                "[workspace.metadata.goosectl]",
+                metadata.workspace_root().join("Cargo.toml"),
+                table,
+                &mut warnings,
+            )?);
+        }
+
+        let file = config_file(path, cwd, metadata.workspace_root())?;
+
+        if let Some(file) = &file {
+            let contents = std::fs::read_to_string(file)
+                .map_err(|e| anyhow!("failed to read `{}`: {e}", file.display()))?;
+            let table = toml_edit::de::from_str(&contents)
+                .map_err(|e| anyhow!("failed to parse `{}`: {e}", file.display()))?;
+
+            base = base.merge(Source::new(
+                "TOML file",
+                file.clone(),
+                &table,
+                &mut warnings,
+            )?);
+        }
+
+        let workspace = Resolved::new(base.clone())?;
+
+        let mut packages = HashMap::new();
+        for package in metadata.workspace_members() {
+            let Some(table) = package.metadata.get(MANIFEST_KEY) else {
+                continue;
+            };
+
+            let figment = base.clone().merge(Source::new(
+                "[package.metadata.goosectl]",
+                package.manifest_path.clone().into(),
+                table,
+                &mut warnings,
+            )?);
+
+            packages.insert(package.name.to_string(), Resolved::new(figment)?);
+        }
+
+        Ok(Self {
+            file,
+            workspace,
+            packages,
+            warnings,
+        })
+    }
+
+    /// Problems that did not prevent loading, such as outdated sources.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// The `goosectl.toml` in use, if any.
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    pub fn workspace(&self) -> &Config {
+        &self.workspace.config
+    }
+
+    /// Configuration for `package`, falling back to the workspace configuration
+    /// when the package has no `[package.metadata.goosectl]` table.
+    pub fn package(&self, package: &str) -> &Config {
+        &self.resolved(Some(package)).config
+    }
+
+    /// The version of `package`, validated against its prerelease settings.
+    pub fn package_version(&self, package: &Package) -> Result<SemanticVersion> {
+        let scheme = self.prerelease_scheme(&package.name);
+
+        SemanticVersion::parse_with(package.version.clone(), scheme)
+            .with_context(|| format!("package `{}` has an unsupported version", package.name))
+    }
+
+    /// Prerelease rules that apply to `package`.
+    pub fn prerelease_scheme(&self, package: &str) -> Arc<PrereleaseScheme> {
+        self.resolved(Some(package)).scheme.clone()
+    }
+
+    /// The merged sources behind [`Self::package`], or the workspace when `None`.
+    pub fn figment(&self, package: Option<&str>) -> &Figment {
+        &self.resolved(package).figment
+    }
+
+    fn resolved(&self, package: Option<&str>) -> &Resolved {
+        package
+            .and_then(|p| self.packages.get(p))
+            .unwrap_or(&self.workspace)
+    }
+}
+
+impl Config {
+    pub fn extract(figment: &Figment) -> Result<Self> {
+        check_top_level_keys(figment)?;
+        figment.extract().map_err(describe)
+    }
+}
+
+/// Keys allowed at the top level of a configuration source.
+fn top_level_keys() -> Vec<String> {
+    super::schema()
+        .get("properties")
+        .and_then(serde_json::Value::as_object)
+        .map(|properties| properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Languages are flattened into [`Config`], so serde would report an unknown
+/// top-level key as an unknown language, without its source or path.
+fn check_top_level_keys(figment: &Figment) -> Result<()> {
+    let keys = top_level_keys();
+    let data: Dict = figment.extract().map_err(describe)?;
+
+    let errors = data
+        .into_keys()
+        .filter(|key| !keys.contains(key))
+        .map(|key| {
+            let mut error = figment::Error::from(format!(
+                "unknown key, expected one of `{}`",
+                keys.join("`, `")
+            ))
+            .with_path(&key);
+            error.profile = Some(figment.profile().clone());
+            error.metadata = figment.find_metadata(&key).cloned();
+            error
+        });
+
+    match errors.reduce(figment::Error::chain) {
+        Some(error) => Err(describe(error)),
+        None => Ok(()),
+    }
+}
+
+/// Name of the environment variable that sets the value at `path`.
+pub(super) fn env_var<S: AsRef<str>>(path: &[S]) -> String {
+    let keys: Vec<&str> = path.iter().map(AsRef::as_ref).collect();
+    format!("{ENV_PREFIX}{}", keys.join("__").to_ascii_uppercase())
+}
+
+/// The `goosectl.toml` to read: `path` when given, which must exist, or the
+/// one discovered from `cwd`.
+pub fn config_file(
+    path: Option<&Path>,
+    cwd: &Path,
+    workspace_root: &Path,
+) -> Result<Option<PathBuf>> {
+    match path {
+        Some(path) if !path.is_file() => {
+            bail!("config file `{}` does not exist", path.display());
+        }
+        Some(path) => Ok(Some(path.to_path_buf())),
+        None => Ok(discover(cwd, workspace_root)),
+    }
+}
+
+/// Walk up from `start` looking for `goosectl.toml`, stopping at the
+/// workspace root.
+pub fn discover(start: &Path, workspace_root: &Path) -> Option<PathBuf> {
+    let mut dirs: Vec<&Path> = start
+        .ancestors()
+        .take_while(|dir| dir.starts_with(workspace_root))
+        .collect();
+
+    // running from outside the workspace → only look at the root
+    if dirs.is_empty() {
+        dirs.push(workspace_root);
+    }
+
+    dirs.into_iter()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Serde defaults of [`Config`], so every key has a known source.
+struct Defaults;
+
+impl Provider for Defaults {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::named(DEFAULTS)
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
+        Serialized::defaults(Config::default()).data()
+    }
+}
+
+/// A configuration table read from `goosectl.toml` or a `[*.metadata.goosectl]`
+/// table in a `Cargo.toml`, upgraded to the current configuration version.
+struct Source {
+    name: &'static str,
+    path: PathBuf,
+    value: serde_json::Value,
+}
+
+impl Source {
+    fn new(
+        name: &'static str,
+        path: PathBuf,
+        value: &serde_json::Value,
+        warnings: &mut Vec<String>,
+    ) -> Result<Self> {
+        let label = match name.starts_with('[') {
+            true => format!("{} {name}", path.display()),
+            false => path.display().to_string(),
+        };
+
+        let (value, from) = migrate_value(value).map_err(|e| anyhow!("{label}: {e}"))?;
+
+        if let Some(from) = from {
+            warnings.push(format!(
+                "{label} uses configuration version {from}, migrated in memory to version \
+                 {CURRENT_VERSION}; run `cargo goosectl config migrate` to update it"
+            ));
+        }
+
+        Ok(Self { name, path, value })
+    }
+}
+
+impl Provider for Source {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::from(self.name, self.path.as_path())
+            .interpolater(|_: &Profile, keys: &[&str]| keys.join("."))
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
+        Serialized::defaults(&self.value).data()
+    }
+}
+
+fn describe(error: figment::Error) -> anyhow::Error {
+    let causes: Vec<String> = error
+        .into_iter()
+        .map(|e| {
+            let Some(metadata) = &e.metadata else {
+                return e.kind.to_string();
+            };
+
+            let source = match &metadata.source {
+                Some(source) => format!("{source} ({})", metadata.name),
+                None => metadata.name.to_string(),
+            };
+
+            match (e.path.is_empty(), &metadata.source) {
+                (true, _) => format!("{source}: {}", e.kind),
+                // name the variable rather than the key it maps to
+                (false, None) => format!("{source}: `{}`: {}", env_var(&e.path), e.kind),
+                (false, Some(_)) => {
+                    let profile = e.profile.clone().unwrap_or_default();
+                    let key = metadata.interpolate(&profile, &e.path);
+                    format!("{source}: key `{key}`: {}", e.kind)
+                }
+            }
+        })
+        .collect();
+
+    anyhow!("invalid goosectl configuration:\n  {}", causes.join("\n  "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Workspace;
+
+    const MEMBER: &str = r#"
+[package]
+name = "core"
+version = "1.0.0"
+edition = "2021"
+"#;
+
+    fn load(workspace: &Workspace) -> Result<WorkspaceConfig> {
+        WorkspaceConfig::load(None, workspace.root(), &workspace.metadata())
+    }
+
+    fn origin(config: &WorkspaceConfig, package: Option<&str>, key: &str) -> String {
+        let provenance = crate::config::Provenance::of(config.figment(package)).unwrap();
+        provenance.origin(key).unwrap().to_string()
+    }
+
+    fn write(dir: &Path, contents: &str) -> PathBuf {
+        let path = dir.join(CONFIG_FILE_NAME);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn discover_walks_up_to_workspace_root() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("crates/core/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        let expected = write(root.path(), "version = 1\n");
+
+        assert_eq!(discover(&nested, root.path()), Some(expected));
+    }
+
+    #[test]
+    fn discover_prefers_closest_file() {
+        let root = tempfile::tempdir().unwrap();
+        let member = root.path().join("crates/core");
+        std::fs::create_dir_all(&member).unwrap();
+        write(root.path(), "version = 1\n");
+        let expected = write(&member, "version = 1\n");
+
+        assert_eq!(discover(&member, root.path()), Some(expected));
+    }
+
+    #[test]
+    fn discover_stops_at_workspace_root() {
+        let outer = tempfile::tempdir().unwrap();
+        let root = outer.path().join("workspace");
+        std::fs::create_dir_all(&root).unwrap();
+        write(outer.path(), "version = 1\n");
+
+        assert_eq!(discover(&root, &root), None);
+    }
+
+    #[test]
+    fn load_defaults_without_any_source() {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace.package("core", MEMBER);
+
+        let config = load(&workspace).unwrap();
+
+        assert_eq!(config.workspace().version(), 1);
+        assert_eq!(config.package("core").version(), 1);
+    }
+
+    #[test]
+    fn load_explicit_file_must_exist() {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace.package("core", MEMBER);
+        let missing = workspace.path("missing.toml");
+
+        let err = WorkspaceConfig::load(Some(&missing), workspace.root(), &workspace.metadata())
+            .err()
+            .unwrap();
+
+        assert!(err.to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn load_error_names_file_and_key() {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace
+            .package("core", MEMBER)
+            .file(CONFIG_FILE_NAME, "version = \"one\"\n");
+
+        let message = load(&workspace).err().unwrap().to_string();
+
+        assert!(message.contains(CONFIG_FILE_NAME));
+        assert!(message.contains("`version`"));
+    }
+
+    #[test]
+    fn load_error_names_file_of_unknown_top_level_key() {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace
+            .package("core", MEMBER)
+            .file(CONFIG_FILE_NAME, "verison = 1\n");
+
+        let message = load(&workspace).err().unwrap().to_string();
+
+        assert!(message.contains(CONFIG_FILE_NAME), "{message}");
+        assert!(message.contains("key `verison`"), "{message}");
+    }
+
+    #[test]
+    fn load_from_workspace_metadata() {
+        let workspace = Workspace::new(
+            "[workspace]\nmembers = [\"core\"]\n\n[workspace.metadata.goosectl]\nversion = 1\n",
+        );
+        workspace.package("core", MEMBER);
+
+        let config = load(&workspace).unwrap();
+
+        assert!(origin(&config, None, "version").ends_with("[workspace.metadata.goosectl]"));
+        assert!(
+            origin(&config, Some("core"), "version").ends_with("[workspace.metadata.goosectl]")
+        );
+    }
+
+    #[test]
+    fn config_file_overrides_workspace_metadata() {
+        let workspace = Workspace::new(
+            "[workspace]\nmembers = [\"core\"]\n\n[workspace.metadata.goosectl]\nversion = 1\n",
+        );
+        workspace
+            .package("core", MEMBER)
+            .file(CONFIG_FILE_NAME, "version = 1\n");
+
+        let config = load(&workspace).unwrap();
+
+        assert!(origin(&config, None, "version").ends_with(CONFIG_FILE_NAME));
+    }
+
+    #[test]
+    fn package_metadata_overrides_workspace_sources() {
+        let workspace = Workspace::new(
+            "[workspace]\nmembers = [\"core\", \"cli\"]\n\n[workspace.metadata.goosectl]\nversion = 1\n",
+        );
+        workspace
+            .package(
+                "core",
+                &format!("{MEMBER}\n[package.metadata.goosectl]\nversion = 1\n"),
+            )
+            .package(
+                "cli",
+                "[package]\nname = \"cli\"\nversion = \"1.0.0\"\nedition = \"2021\"\n",
+            )
+            .file(CONFIG_FILE_NAME, "version = 1\n");
+
+        let config = load(&workspace).unwrap();
+
+        assert!(origin(&config, None, "version").ends_with(CONFIG_FILE_NAME));
+        assert!(origin(&config, Some("core"), "version").ends_with("[package.metadata.goosectl]"));
+        assert!(origin(&config, Some("cli"), "version").ends_with(CONFIG_FILE_NAME));
+    }
+
+    #[test]
+    fn load_rejects_future_version() {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace
+            .package("core", MEMBER)
+            .file(CONFIG_FILE_NAME, "version = 99\n");
+
+        let message = load(&workspace).err().unwrap().to_string();
+
+        assert!(message.contains(CONFIG_FILE_NAME));
+        assert!(message.contains("upgrade goosectl"));
+    }
+
+    #[test]
+    fn load_current_version_has_no_warnings() {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace
+            .package("core", MEMBER)
+            .file(CONFIG_FILE_NAME, "version = 1\n");
+
+        assert!(load(&workspace).unwrap().warnings().is_empty());
+    }
+
+    #[test]
+    fn package_version_uses_package_prerelease_identifiers() {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace
+            .package(
+                "core",
+                "[package]\nname = \"core\"\nversion = \"1.0.0-preview.1\"\nedition = \"2021\"\n\n\
+                 [package.metadata.goosectl.project.prerelease]\nidentifiers = [\"alpha\", \"beta\"]\n",
+            )
+            .file(CONFIG_FILE_NAME, "[project.prerelease]\nidentifiers = [\"preview\"]\n");
+        let metadata = workspace.metadata();
+        let config = WorkspaceConfig::load(None, workspace.root(), &metadata).unwrap();
+
+        let err = config
+            .package_version(metadata.workspace_members()[0])
+            .unwrap_err();
+
+        assert!(format!("{err:#}").contains("`preview` is not allowed"));
+    }
+
+    #[test]
+    fn load_rejects_invalid_identifier_list() {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace
+            .package("core", MEMBER)
+            .file(CONFIG_FILE_NAME, "[project.prerelease]\nidentifiers = []\n");
+
+        assert!(load(&workspace).is_err());
+    }
+
+    #[test]
+    fn env_var_names_nested_keys() {
+        assert_eq!(env_var(&["version"]), "GOOSECTL_VERSION");
+        assert_eq!(env_var(&["project", "mode"]), "GOOSECTL_PROJECT__MODE");
+    }
+
+    #[test]
+    fn package_metadata_error_names_manifest() {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace.package(
+            "core",
+            &format!("{MEMBER}\n[package.metadata.goosectl]\nversion = \"nine\"\n"),
+        );
+
+        let message = load(&workspace).err().unwrap().to_string();
+
+        assert!(message.contains("core/Cargo.toml"));
+        assert!(message.contains("[package.metadata.goosectl]"));
+        assert!(message.contains("`version`"));
+    }
+}
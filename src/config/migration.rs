@@ -0,0 +1,220 @@
+use anyhow::{Result, anyhow, bail};
+use toml_edit::{DocumentMut, TableLike};
+
+/// Configuration format understood by this version of goosectl.
+pub const CURRENT_VERSION: u64 = 1;
+
+/// Version assumed when a configuration does not declare one.
+pub(super) const UNVERSIONED: u64 = 1;
+
+/// Upgrades a configuration table from `from` to `from + 1`.
+pub struct Migration {
+    pub from: u64,
+    pub apply: fn(&mut dyn TableLike) -> Result<()>,
+}
+
+/// Every migration step, oldest first.
+pub const MIGRATIONS: &[Migration] = &[];
+
+/// Upgrade `table` to [`CURRENT_VERSION`] in place, returning the version it
+/// was upgraded from, or `None` if it was already current.
+pub fn migrate(table: &mut dyn TableLike) -> Result<Option<u64>> {
+    migrate_with(table, MIGRATIONS, CURRENT_VERSION)
+}
+
+/// Migrate the goosectl table found under `path` in `doc` (empty for a
+/// standalone `goosectl.toml`), preserving comments and formatting.
+pub fn migrate_document(doc: &mut DocumentMut, path: &[&str]) -> Result<Option<u64>> {
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+
+    for key in path {
+        table = table
+            .get_mut(key)
+            .and_then(|item| item.as_table_like_mut())
+            .ok_or_else(|| anyhow!("missing table `{}`", path.join(".")))?;
+    }
+
+    migrate(table)
+}
+
+/// Migrate a configuration table read as JSON, e.g. from cargo metadata.
+pub fn migrate_value(value: &serde_json::Value) -> Result<(serde_json::Value, Option<u64>)> {
+    let mut doc = toml_edit::ser::to_document(value)?;
+    let from = migrate(doc.as_table_mut())?;
+
+    match from {
+        Some(_) => Ok((toml_edit::de::from_document(doc)?, from)),
+        None => Ok((value.clone(), None)),
+    }
+}
+
+fn migrate_with(
+    table: &mut dyn TableLike,
+    migrations: &[Migration],
+    current: u64,
+) -> Result<Option<u64>> {
+    let from = match table.get("version") {
+        None => UNVERSIONED,
+        Some(item) => item
+            .as_integer()
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or_else(|| anyhow!("`version` must be a positive integer"))?,
+    };
+
+    if from > current {
+        bail!(
+            "configuration version {from} is newer than the latest supported version \
+             ({current}); please upgrade goosectl"
+        );
+    }
+
+    if from == current {
+        return Ok(None);
+    }
+
+    let mut version = from;
+    while version < current {
+        let step = migrations
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or_else(|| anyhow!("no migration from configuration version {version}"))?;
+
+        (step.apply)(table)?;
+        version += 1;
+    }
+
+    set_version(table, version);
+
+    Ok(Some(from))
+}
+
+fn set_version(table: &mut dyn TableLike, version: u64) {
+    let new = toml_edit::Value::from(version as i64);
+
+    match table
+        .get_mut("version")
+        .and_then(|item| item.as_value_mut())
+    {
+        // keep the surrounding whitespace and any trailing comment
+        Some(value) => {
+            let decor = value.decor().clone();
+            *value = new;
+            *value.decor_mut() = decor;
+        }
+        None => {
+            table.insert("version", toml_edit::Item::Value(new));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename_mode(table: &mut dyn TableLike) -> Result<()> {
+        if let Some(mode) = table.remove("mode") {
+            table.insert("versioning", mode);
+        }
+        Ok(())
+    }
+
+    fn noop(_: &mut dyn TableLike) -> Result<()> {
+        Ok(())
+    }
+
+    const CHAIN: &[Migration] = &[
+        Migration {
+            from: 1,
+            apply: rename_mode,
+        },
+        Migration {
+            from: 2,
+            apply: noop,
+        },
+    ];
+
+    fn doc(s: &str) -> DocumentMut {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn current_version_is_untouched() {
+        let mut doc = doc("version = 1\n");
+
+        assert_eq!(migrate(doc.as_table_mut()).unwrap(), None);
+        assert_eq!(doc.to_string(), "version = 1\n");
+    }
+
+    #[test]
+    fn future_version_asks_for_upgrade() {
+        let mut doc = doc("version = 99\n");
+
+        let err = migrate(doc.as_table_mut()).unwrap_err();
+
+        assert!(err.to_string().contains("upgrade goosectl"));
+    }
+
+    #[test]
+    fn chain_runs_every_step_in_order() {
+        let mut doc = doc("# settings\nversion = 1 # format\nmode = \"lockstep\"\n");
+
+        let from = migrate_with(doc.as_table_mut(), CHAIN, 3).unwrap();
+
+        assert_eq!(from, Some(1));
+        assert_eq!(
+            doc.to_string(),
+            "# settings\nversion = 3 # format\nversioning = \"lockstep\"\n"
+        );
+    }
+
+    #[test]
+    fn missing_version_is_treated_as_unversioned() {
+        let mut doc = doc("mode = \"lockstep\"\n");
+
+        let from = migrate_with(doc.as_table_mut(), CHAIN, 3).unwrap();
+
+        assert_eq!(from, Some(UNVERSIONED));
+        assert!(doc.to_string().contains("version = 3"));
+    }
+
+    #[test]
+    fn gap_in_chain_fails() {
+        let mut doc = doc("version = 1\n");
+
+        let err = migrate_with(doc.as_table_mut(), &CHAIN[..1], 3).unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("no migration from configuration version 2")
+        );
+    }
+
+    #[test]
+    fn migrate_document_in_manifest() {
+        let mut doc =
+            doc("[package]\nname = \"core\"\n\n[package.metadata.goosectl]\nversion = 1\n");
+
+        let from = migrate_document(&mut doc, &["package", "metadata", "goosectl"]).unwrap();
+
+        assert_eq!(from, None);
+    }
+
+    #[test]
+    fn migrate_document_missing_table() {
+        let mut doc = doc("[package]\nname = \"core\"\n");
+
+        let result = migrate_document(&mut doc, &["package", "metadata", "goosectl"]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn migrate_value_passes_current_through() {
+        let value = serde_json::json!({ "version": 1 });
+
+        let (migrated, from) = migrate_value(&value).unwrap();
+
+        assert_eq!(migrated, value);
+        assert_eq!(from, None);
+    }
+}
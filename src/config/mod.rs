@@ -1,15 +1,31 @@
 use std::collections::HashMap;
 
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+mod cascade;
 mod language;
+mod loader;
+mod migration;
+mod prerelease;
+mod provenance;
+mod schema;
 
-pub use language::{Language, LanguageConfig};
+pub use cascade::{CascadeConfig, CascadeLevel};
+pub use language::{Language, LanguageConfig, RequirementStyle};
+pub use loader::{
+    CONFIG_FILE_NAME, ENV_PREFIX, MANIFEST_KEY, WorkspaceConfig, config_file, discover,
+};
+pub use migration::{CURRENT_VERSION, Migration, migrate, migrate_document};
+pub use prerelease::PrereleaseConfig;
+pub use provenance::Provenance;
+pub use schema::{schema, validate};
 
-#[derive(Debug, Deserialize, JsonSchema)]
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct Config {
     #[serde(default)]
+    #[schemars(range(min = migration::UNVERSIONED))]
     version: GooseConfigVersion,
 
     #[serde(default)]
@@ -31,17 +47,77 @@ impl Config {
     pub fn langauge(&self) -> &HashMap<Language, LanguageConfig> {
         &self.language
     }
+
+    /// Settings for `language`, or the defaults when it has none.
+    pub fn language_config(&self, language: &Language) -> LanguageConfig {
+        self.language.get(language).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct GooseConfig {
+    #[serde(default)]
+    versioning: VersioningMode,
+
+    #[serde(default)]
+    prerelease: PrereleaseConfig,
+
+    #[serde(default)]
+    broken_dependents: BrokenDependents,
+
+    #[serde(default)]
+    cascade: CascadeConfig,
 }
 
-#[derive(Debug, Deserialize, Default, JsonSchema)]
-pub struct GooseConfig {}
+impl GooseConfig {
+    pub fn versioning(&self) -> VersioningMode {
+        self.versioning
+    }
+
+    pub fn broken_dependents(&self) -> BrokenDependents {
+        self.broken_dependents
+    }
+
+    pub fn cascade(&self) -> CascadeConfig {
+        self.cascade
+    }
+
+    pub fn prerelease(&self) -> &PrereleaseConfig {
+        &self.prerelease
+    }
+}
+
+/// How versions of workspace members relate to each other. Informational
+/// only: it is recorded by `init` but not enforced when bumping.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VersioningMode {
+    /// Every member is released on its own version line.
+    #[default]
+    Independent,
+    /// All members are meant to share a single version.
+    Lockstep,
+}
+
+/// What to do when a bump leaves workspace members requiring a version of a
+/// bumped package that it no longer has.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrokenDependents {
+    /// Report each broken requirement and bump anyway.
+    #[default]
+    Warn,
+    /// Refuse to bump.
+    Fail,
+}
 
-#[derive(Debug, Deserialize, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(transparent)]
 struct GooseConfigVersion(u64);
 
 impl Default for GooseConfigVersion {
     fn default() -> Self {
-        Self(1)
+        Self(migration::UNVERSIONED)
     }
 }
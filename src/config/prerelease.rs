@@ -0,0 +1,86 @@
+use anyhow::{Result, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::version::scheme::{PrereleaseFormat, PrereleaseScheme};
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct PrereleaseConfig {
+    /// Allowed prerelease identifiers, from earliest to latest in the release
+    /// cycle (e.g. `["dev", "alpha", "beta", "rc"]`). When unset, any
+    /// identifier is accepted and identifiers are ordered alphabetically.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    identifiers: Option<Vec<String>>,
+
+    /// Regex describing the prerelease, with `ident` and `counter` named
+    /// captures (e.g. `(?<ident>[a-z]+)(?<counter>\d+)` for `rc1`). The whole
+    /// prerelease must match. Requires `template`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+
+    /// How to write a prerelease back, using `{ident}` and `{counter}`
+    /// placeholders (e.g. `{ident}{counter}`). Requires `pattern`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+}
+
+impl PrereleaseConfig {
+    pub fn identifiers(&self) -> Option<&[String]> {
+        self.identifiers.as_deref()
+    }
+
+    pub fn scheme(&self) -> Result<PrereleaseScheme> {
+        let format = match (&self.pattern, &self.template) {
+            (Some(pattern), Some(template)) => PrereleaseFormat::custom(pattern, template)?,
+            (None, None) => PrereleaseFormat::Strict,
+            _ => bail!("`pattern` and `template` must be set together"),
+        };
+
+        let scheme = PrereleaseScheme::default().with_format(format);
+
+        match &self.identifiers {
+            Some(identifiers) => scheme.with_identifiers(identifiers.clone()),
+            None => Ok(scheme),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> PrereleaseConfig {
+        toml_edit::de::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn default_is_strict() {
+        let scheme = config("").scheme().unwrap();
+
+        assert_eq!(scheme, PrereleaseScheme::default());
+    }
+
+    #[test]
+    fn custom_format_and_identifiers() {
+        let scheme = config(
+            r#"
+            pattern = '(?<ident>[a-z]+)(?<counter>\d+)'
+            template = "{ident}{counter}"
+            identifiers = ["alpha", "beta"]
+            "#,
+        )
+        .scheme()
+        .unwrap();
+
+        assert_eq!(scheme.parse("beta3").unwrap().iteration, 3);
+        assert!(scheme.check("rc").is_err());
+    }
+
+    #[test]
+    fn pattern_requires_template() {
+        let result = config(r"pattern = '(?<ident>[a-z]+)(?<counter>\d+)'").scheme();
+
+        assert!(result.is_err());
+    }
+}
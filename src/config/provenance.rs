@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use figment::{Figment, value::Value};
+
+use super::loader::{DEFAULTS, env_var};
+
+/// The merged configuration together with the source of every value.
+pub struct Provenance {
+    value: serde_json::Value,
+    origins: BTreeMap<String, String>,
+}
+
+impl Provenance {
+    pub fn of(figment: &Figment) -> Result<Self> {
+        let merged = figment.find_value("")?;
+
+        let mut origins = BTreeMap::new();
+        collect(figment, &merged, &mut Vec::new(), &mut origins);
+
+        Ok(Self {
+            value: serde_json::to_value(&merged)?,
+            origins,
+        })
+    }
+
+    /// Where the value at the dotted `key` came from.
+    pub fn origin(&self, key: &str) -> Option<&str> {
+        self.origins.get(key).map(String::as_str)
+    }
+
+    /// The configuration as TOML, each value followed by a comment naming its source.
+    pub fn to_toml(&self) -> Result<String> {
+        let mut doc = toml_edit::ser::to_document(&self.value)?;
+        annotate_table(doc.as_table_mut(), "", &self.origins);
+        Ok(doc.to_string())
+    }
+
+    /// The configuration as JSON, each value replaced by `{ "value", "source" }`.
+    pub fn to_json(&self) -> serde_json::Value {
+        annotate_json(&self.value, "", &self.origins)
+    }
+}
+
+fn collect<'a>(
+    figment: &Figment,
+    value: &'a Value,
+    path: &mut Vec<&'a str>,
+    origins: &mut BTreeMap<String, String>,
+) {
+    if let Value::Dict(_, dict) = value {
+        for (key, child) in dict {
+            path.push(key);
+            collect(figment, child, path, origins);
+            path.pop();
+        }
+        return;
+    }
+
+    let origin = match figment.get_metadata(value.tag()) {
+        Some(md) if md.name == DEFAULTS => "default".to_string(),
+        Some(md) => match &md.source {
+            Some(source) if md.name.starts_with('[') => format!("file {source} {}", md.name),
+            Some(source) => format!("file {source}"),
+            None => format!("env {}", env_var(path)),
+        },
+        None => "unknown".to_string(),
+    };
+
+    origins.insert(path.join("."), origin);
+}
+
+fn annotate_table(table: &mut toml_edit::Table, prefix: &str, origins: &BTreeMap<String, String>) {
+    for (mut key, item) in table.iter_mut() {
+        let path = join(prefix, key.get());
+
+        // render nested dictionaries as `[section]` headers
+        if let Some(inline) = item.as_inline_table() {
+            *item = toml_edit::Item::Table(inline.clone().into_table());
+        }
+
+        match item {
+            toml_edit::Item::Table(child) => annotate_table(child, &path, origins),
+            toml_edit::Item::Value(value) => {
+                if let Some(origin) = origins.get(&path) {
+                    value.decor_mut().set_suffix(format!("  # {origin}"));
+                }
+                key.leaf_decor_mut().clear();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn annotate_json(
+    value: &serde_json::Value,
+    prefix: &str,
+    origins: &BTreeMap<String, String>,
+) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(object) => object
+            .iter()
+            .map(|(key, child)| {
+                let path = join(prefix, key);
+                (key.clone(), annotate_json(child, &path, origins))
+            })
+            .collect(),
+        leaf => serde_json::json!({
+            "value": leaf,
+            "source": origins.get(prefix),
+        }),
+    }
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    match prefix.is_empty() {
+        true => key.to_string(),
+        false => format!("{prefix}.{key}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{CONFIG_FILE_NAME, WorkspaceConfig},
+        testing::Workspace,
+    };
+
+    fn provenance(workspace: &Workspace, package: Option<&str>) -> Provenance {
+        let config = WorkspaceConfig::load(None, workspace.root(), &workspace.metadata()).unwrap();
+        Provenance::of(config.figment(package)).unwrap()
+    }
+
+    fn workspace() -> Workspace {
+        let workspace = Workspace::new("[workspace]\nmembers = [\"core\"]\n");
+        workspace.package(
+            "core",
+            "[package]\nname = \"core\"\nversion = \"1.0.0\"\nedition = \"2021\"\n",
+        );
+        workspace
+    }
+
+    #[test]
+    fn origin_defaults() {
+        let workspace = workspace();
+
+        let provenance = provenance(&workspace, None);
+
+        assert_eq!(provenance.origin("version"), Some("default"));
+    }
+
+    #[test]
+    fn origin_config_file() {
+        let workspace = workspace();
+        workspace.file(CONFIG_FILE_NAME, "version = 1\n");
+
+        let provenance = provenance(&workspace, None);
+
+        assert!(provenance.origin("version").unwrap().starts_with("file "));
+        assert!(
+            provenance
+                .origin("version")
+                .unwrap()
+                .ends_with(CONFIG_FILE_NAME)
+        );
+    }
+
+    #[test]
+    fn origin_package_metadata() {
+        let workspace = workspace();
+        workspace.package(
+            "core",
+            "[package]\nname = \"core\"\nversion = \"1.0.0\"\nedition = \"2021\"\n\n\
+             [package.metadata.goosectl]\nversion = 1\n",
+        );
+
+        let provenance = provenance(&workspace, Some("core"));
+
+        assert!(
+            provenance
+                .origin("version")
+                .unwrap()
+                .ends_with("Cargo.toml [package.metadata.goosectl]")
+        );
+    }
+
+    #[test]
+    fn toml_annotates_values() {
+        let workspace = workspace();
+
+        let toml = provenance(&workspace, None).to_toml().unwrap();
+
+        assert!(toml.contains("version = 1  # default"));
+    }
+
+    #[test]
+    fn json_wraps_values() {
+        let workspace = workspace();
+
+        let json = provenance(&workspace, None).to_json();
+
+        assert_eq!(json["version"]["value"], 1);
+        assert_eq!(json["version"]["source"], "default");
+    }
+}
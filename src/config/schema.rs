@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use jsonschema::{
+    ValidationError,
+    error::{TypeKind, ValidationErrorKind},
+};
+use schemars::{Schema, schema_for};
+use serde_json::Value;
+
+use super::{Config, migration::migrate_value};
+
+/// JSON schema for `goosectl.toml`, shared by `config validate` and the
+/// checked-in `schemas/` output.
+pub fn schema() -> Schema {
+    schema_for!(Config)
+}
+
+/// Check `value` against the configuration schema, returning one message per
+/// violation. Unlike deserialization, unknown keys are reported.
+pub fn validate(value: &Value) -> Vec<String> {
+    let schema = schema();
+    let validator =
+        jsonschema::validator_for(schema.as_value()).expect("generated schema is valid");
+    let mut errors: Vec<String> = validator.iter_errors(value).flat_map(describe).collect();
+
+    // some rules cannot be expressed in the schema, e.g. non-empty identifiers
+    if errors.is_empty()
+        && let Err(e) = load_checks(value)
+    {
+        errors.push(format!("{e:#}"));
+    }
+
+    errors
+}
+
+/// The checks loading makes beyond the schema, so that whatever passes
+/// validation also loads.
+fn load_checks(value: &Value) -> Result<()> {
+    let (value, _) = migrate_value(value)?;
+    let config: Config = serde_json::from_value(value)?;
+
+    config
+        .project()
+        .prerelease()
+        .scheme()
+        .with_context(|| display("project.prerelease"))?;
+
+    Ok(())
+}
+
+/// One message per violation, naming the offending key.
+fn describe(error: ValidationError<'_>) -> Vec<String> {
+    let path = error
+        .instance_path()
+        .as_str()
+        .split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect::<Vec<_>>()
+        .join(".");
+
+    match error.kind() {
+        ValidationErrorKind::AdditionalProperties { unexpected } => unexpected
+            .iter()
+            .map(|key| format!("{}: unknown key", display(&join(&path, key))))
+            .collect(),
+        ValidationErrorKind::Minimum { limit } => {
+            vec![format!("{}: must be at least {limit}", display(&path))]
+        }
+        ValidationErrorKind::Type {
+            kind: TypeKind::Single(expected),
+        } => vec![format!(
+            "{}: expected {expected}, found {}",
+            display(&path),
+            type_name(error.instance())
+        )],
+        _ => vec![format!("{}: {error}", display(&path))],
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    match path.is_empty() {
+        true => key.to_string(),
+        false => format!("{path}.{key}"),
+    }
+}
+
+fn display(path: &str) -> String {
+    match path.is_empty() {
+        true => "(root)".to_string(),
+        false => format!("`{path}`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn validate_accepts_known_keys() {
+        let errors = validate(&json!({ "version": 1, "project": {}, "rust": {} }));
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn validate_accepts_empty_config() {
+        assert!(validate(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_version_zero() {
+        let errors = validate(&json!({ "version": 0 }));
+
+        assert_eq!(errors, vec!["`version`: must be at least 1"]);
+    }
+
+    #[test]
+    fn validate_rejects_what_load_rejects() {
+        let empty = validate(&json!({ "project": { "prerelease": { "identifiers": [] } } }));
+        let unpaired = validate(&json!({ "project": { "prerelease": { "template": "{ident}" } } }));
+        let newer = validate(&json!({ "version": 99 }));
+
+        assert_eq!(empty.len(), 1, "{empty:?}");
+        assert!(empty[0].starts_with("`project.prerelease`"), "{empty:?}");
+        assert!(unpaired[0].contains("set together"), "{unpaired:?}");
+        assert!(newer[0].contains("newer"), "{newer:?}");
+    }
+
+    #[test]
+    fn validate_rejects_unknown_top_level_key() {
+        let errors = validate(&json!({ "verison": 1 }));
+
+        assert_eq!(errors, vec!["`verison`: unknown key"]);
+    }
+
+    #[test]
+    fn validate_rejects_unknown_nested_key() {
+        let errors = validate(&json!({ "project": { "mode": "lockstep" } }));
+
+        assert_eq!(errors, vec!["`project.mode`: unknown key"]);
+    }
+
+    #[test]
+    fn validate_accepts_versioning_mode() {
+        let errors = validate(&json!({ "project": { "versioning": "lockstep" } }));
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn validate_rejects_unknown_versioning_mode() {
+        let errors = validate(&json!({ "project": { "versioning": "sideways" } }));
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("`project.versioning`"));
+    }
+
+    #[test]
+    fn validate_rejects_wrong_type() {
+        let errors = validate(&json!({ "version": "one" }));
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("`version`: expected"));
+    }
+
+    #[test]
+    fn validate_names_array_items() {
+        let errors =
+            validate(&json!({ "project": { "prerelease": { "identifiers": ["rc", 1] } } }));
+
+        assert_eq!(
+            errors,
+            vec!["`project.prerelease.identifiers.1`: expected string, found number"]
+        );
+    }
+}
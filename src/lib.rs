@@ -1,7 +1,11 @@
 #[cfg(feature = "cli")]
 pub mod cli;
 
+pub mod bump;
 pub mod config;
+pub mod manifest;
 pub mod metadata;
+#[cfg(test)]
+mod testing;
 pub mod utils;
 pub mod version;
@@ -0,0 +1,453 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+/// Dependency tables Cargo understands, at the top level or under `[target.*]`.
+pub const DEPENDENCY_KINDS: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Read and parse a `Cargo.toml`, keeping its formatting.
+pub fn read(path: &Path) -> Result<DocumentMut> {
+    read_raw(path).map(|(_, doc)| doc)
+}
+
+/// Read and parse a `Cargo.toml`, also returning its contents exactly as they
+/// are on disk.
+pub fn read_raw(path: &Path) -> Result<(String, DocumentMut)> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read `{}`", path.display()))?;
+
+    let doc = contents
+        .parse::<DocumentMut>()
+        .with_context(|| format!("failed to parse `{}`", path.display()))?;
+
+    Ok((contents, doc))
+}
+
+/// Write a manifest back, refusing any path outside `workspace_root` so that
+/// registry sources and crates elsewhere on disk are never modified.
+pub fn write(workspace_root: &Path, path: &Path, doc: &DocumentMut) -> Result<()> {
+    ensure_inside(workspace_root, path)?;
+
+    let original = std::fs::read_to_string(path).unwrap_or_default();
+    replace(path, &render(doc, &original))
+}
+
+/// Serialize `doc` with the line endings of `original`, the contents it was
+/// read from, so that a CRLF manifest stays CRLF.
+pub fn render(doc: &DocumentMut, original: &str) -> String {
+    let contents = doc.to_string();
+
+    if original.contains("\r\n") {
+        contents.replace("\r\n", "\n").replace('\n', "\r\n")
+    } else {
+        contents
+    }
+}
+
+/// Fail unless `path` lies within `workspace_root`.
+pub fn ensure_inside(workspace_root: &Path, path: &Path) -> Result<()> {
+    let root = workspace_root
+        .canonicalize()
+        .with_context(|| format!("failed to resolve `{}`", workspace_root.display()))?;
+    let target = path
+        .canonicalize()
+        .with_context(|| format!("failed to resolve `{}`", path.display()))?;
+
+    if !target.starts_with(&root) {
+        bail!(
+            "refusing to write `{}`: it is outside the workspace root `{}`",
+            path.display(),
+            workspace_root.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Replace the contents of `path` through a temporary file next to it, so a
+/// failed write never leaves it truncated.
+///
+/// A symlink is followed so the file it points at is replaced rather than the
+/// link, and the file keeps its permissions.
+pub fn replace(path: &Path, contents: &str) -> Result<()> {
+    let target = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let temp = target.with_file_name(format!(".{file_name}.goosectl.tmp"));
+
+    let result = std::fs::write(&temp, contents)
+        .and_then(|()| match std::fs::metadata(&target) {
+            Ok(metadata) => std::fs::set_permissions(&temp, metadata.permissions()),
+            Err(_) => Ok(()),
+        })
+        .and_then(|()| std::fs::rename(&temp, &target));
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+
+    result.with_context(|| format!("failed to write `{}`", path.display()))
+}
+
+/// Whether the manifest takes its version from `[workspace.package]`
+/// (`version.workspace = true`).
+pub fn inherits_version(doc: &DocumentMut) -> bool {
+    doc.get("package")
+        .and_then(|package| package.get("version"))
+        .and_then(Item::as_table_like)
+        .and_then(|version| version.get("workspace"))
+        .and_then(Item::as_bool)
+        .unwrap_or(false)
+}
+
+/// The `[workspace.package]` version of a root manifest, if it sets one.
+pub fn workspace_version_mut(doc: &mut DocumentMut) -> Option<&mut Item> {
+    doc.get_mut("workspace")?
+        .get_mut("package")?
+        .get_mut("version")
+        .filter(|version| version.is_str())
+}
+
+/// Every dependency table in the manifest, including the
+/// `[target.'cfg(..)'.*]` variants, whichever way they are written, with the
+/// keys leading to it (e.g. `["target", "cfg(unix)", "dependencies"]`).
+pub fn dependency_tables_mut(doc: &mut DocumentMut) -> Vec<(Vec<String>, &mut dyn TableLike)> {
+    let mut tables = Vec::new();
+
+    for (key, item) in doc.as_table_mut().iter_mut() {
+        if DEPENDENCY_KINDS.contains(&key.get()) {
+            let path = vec![key.get().to_string()];
+            tables.extend(item.as_table_like_mut().map(|deps| (path, deps)));
+            continue;
+        }
+
+        if key.get() != "target" {
+            continue;
+        }
+
+        let Some(targets) = item.as_table_like_mut() else {
+            continue;
+        };
+
+        for (cfg, target) in targets.iter_mut() {
+            let cfg = cfg.get().to_string();
+            let Some(target) = target.as_table_like_mut() else {
+                continue;
+            };
+
+            for (kind, deps) in target.iter_mut() {
+                if DEPENDENCY_KINDS.contains(&kind.get()) {
+                    let path = vec!["target".to_string(), cfg.clone(), kind.get().to_string()];
+                    tables.extend(deps.as_table_like_mut().map(|deps| (path, deps)));
+                }
+            }
+        }
+    }
+
+    tables
+}
+
+/// The package a detailed dependency entry refers to: the `package` field of
+/// a renamed dependency, or its key otherwise.
+pub fn dependency_package(key: &str, dependency: &dyn TableLike) -> String {
+    dependency
+        .get("package")
+        .and_then(Item::as_str)
+        .unwrap_or(key)
+        .to_string()
+}
+
+/// Whether the `path` of a dependency declared in the manifest at
+/// `manifest_dir` leads to `package_dir`.
+pub fn path_points_at(manifest_dir: &Path, dependency: &dyn TableLike, package_dir: &Path) -> bool {
+    let Some(path) = dependency.get("path").and_then(Item::as_str) else {
+        return false;
+    };
+
+    match (
+        manifest_dir.join(path).canonicalize(),
+        package_dir.canonicalize(),
+    ) {
+        (Ok(target), Ok(package_dir)) => target == package_dir,
+        _ => false,
+    }
+}
+
+/// Set the `version` requirement of a detailed dependency entry.
+pub fn set_dependency_version(dependency: &mut dyn TableLike, version: impl Into<String>) {
+    match dependency.get_mut("version") {
+        Some(current) => set_str(current, version),
+        None => {
+            // the space before the `}` of an inline table hangs off its last value
+            let padding = dependency
+                .iter_mut()
+                .last()
+                .and_then(|(_, item)| item.as_value_mut())
+                .and_then(|value| {
+                    let suffix = value.decor().suffix()?.as_str()?.to_string();
+                    suffix.trim().is_empty().then(|| {
+                        value.decor_mut().set_suffix("");
+                        suffix
+                    })
+                });
+
+            dependency.insert("version", toml_edit::value(version.into()));
+
+            if let (Some(padding), Some(value)) = (
+                padding,
+                dependency.get_mut("version").and_then(Item::as_value_mut),
+            ) {
+                value.decor_mut().set_suffix(padding);
+            }
+        }
+    }
+}
+
+/// Move the `Cargo.lock` entry of the member `name` from `from` to `to`, along
+/// with the references to it that spell out the version. Returns the index of
+/// the entry in `[[package]]`, if one was found.
+pub fn set_locked_version(
+    lock: &mut DocumentMut,
+    name: &str,
+    from: &str,
+    to: &str,
+) -> Option<usize> {
+    let entries = lock
+        .get_mut("package")
+        .and_then(Item::as_array_of_tables_mut)?;
+
+    let mut found = None;
+    for (index, entry) in entries.iter_mut().enumerate() {
+        // workspace members are the only entries without a `source`
+        if found.is_none()
+            && !entry.contains_key("source")
+            && entry.get("name").and_then(Item::as_str) == Some(name)
+            && entry.get("version").and_then(Item::as_str) == Some(from)
+        {
+            set_str(&mut entry["version"], to);
+            found = Some(index);
+        }
+
+        // `name version` is used when several versions of a package are locked
+        let Some(dependencies) = entry.get_mut("dependencies").and_then(Item::as_array_mut) else {
+            continue;
+        };
+
+        for dependency in dependencies.iter_mut() {
+            if dependency.as_str() == Some(&format!("{name} {from}")) {
+                let decor = dependency.decor().clone();
+                *dependency = Value::from(format!("{name} {to}"));
+                *dependency.decor_mut() = decor;
+            }
+        }
+    }
+
+    found
+}
+
+/// Replace a string value, keeping the whitespace and comments around it.
+pub fn set_str(item: &mut Item, value: impl Into<String>) {
+    let Some(current) = item.as_value_mut() else {
+        *item = toml_edit::value(value.into());
+        return;
+    };
+
+    let decor = current.decor().clone();
+    *current = Value::from(value.into());
+    *current.decor_mut() = decor;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(s: &str) -> DocumentMut {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn detects_inherited_version() {
+        assert!(inherits_version(&doc(
+            "[package]\nname = \"a\"\nversion.workspace = true\n"
+        )));
+        assert!(inherits_version(&doc(
+            "[package]\nname = \"a\"\nversion = { workspace = true }\n"
+        )));
+        assert!(!inherits_version(&doc(
+            "[package]\nname = \"a\"\nversion = \"1.0.0\"\n"
+        )));
+    }
+
+    #[test]
+    fn finds_workspace_version() {
+        let mut root = doc("[workspace.package]\nversion = \"1.0.0\"\n");
+
+        assert!(workspace_version_mut(&mut root).is_some());
+        assert!(workspace_version_mut(&mut doc("[workspace]\nmembers = []\n")).is_none());
+    }
+
+    #[test]
+    fn finds_every_dependency_table() {
+        let mut doc = doc(r#"
+[dependencies]
+a = "1"
+
+[dev-dependencies.b]
+version = "1"
+
+[target.'cfg(unix)'.dependencies]
+c = "1"
+
+[target.'cfg(windows)'.build-dependencies]
+d = "1"
+
+[target.x86_64-pc-windows-gnu]
+dev-dependencies = { e = "1" }
+
+[features]
+f = []
+"#);
+
+        let entries: Vec<String> = dependency_tables_mut(&mut doc)
+            .into_iter()
+            .flat_map(|(path, deps)| {
+                deps.iter()
+                    .map(|(name, _)| format!("{}.{name}", path.join(".")))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        assert_eq!(
+            entries,
+            [
+                "dependencies.a",
+                "dev-dependencies.b",
+                "target.cfg(unix).dependencies.c",
+                "target.cfg(windows).build-dependencies.d",
+                "target.x86_64-pc-windows-gnu.dev-dependencies.e",
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_renamed_package() {
+        let doc = doc(r#"
+[dependencies]
+core = { package = "my-core", path = "../core" }
+util = { path = "../util" }
+"#);
+        let deps = doc["dependencies"].as_table_like().unwrap();
+
+        let package =
+            |key| dependency_package(key, deps.get(key).unwrap().as_table_like().unwrap());
+
+        assert_eq!(package("core"), "my-core");
+        assert_eq!(package("util"), "util");
+    }
+
+    #[test]
+    fn path_must_lead_to_package() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("core")).unwrap();
+        std::fs::create_dir_all(dir.path().join("app")).unwrap();
+        std::fs::create_dir_all(dir.path().join("vendor/core")).unwrap();
+
+        let doc = doc(r#"
+[dependencies]
+core = { path = "../core" }
+other = { path = "../vendor/core" }
+registry = { version = "1" }
+"#);
+        let deps = doc["dependencies"].as_table_like().unwrap();
+        let points_at = |key| {
+            path_points_at(
+                &dir.path().join("app"),
+                deps.get(key).unwrap().as_table_like().unwrap(),
+                &dir.path().join("core"),
+            )
+        };
+
+        assert!(points_at("core"));
+        assert!(!points_at("other"));
+        assert!(!points_at("registry"));
+    }
+
+    #[test]
+    fn refuses_to_write_outside_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("workspace");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+
+        let doc = doc("[package]\nname = \"a\"\n");
+
+        assert!(write(&root, &root.join("Cargo.toml"), &doc).is_ok());
+        assert!(write(&root, &root.join("../Cargo.toml"), &doc).is_err());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("Cargo.toml")).unwrap(),
+            ""
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn replace_keeps_symlinks_and_permissions() {
+        use std::os::unix::fs::{PermissionsExt, symlink};
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("shared.toml");
+        let link = dir.path().join("Cargo.toml");
+        std::fs::write(&target, "before").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o640)).unwrap();
+        symlink(&target, &link).unwrap();
+
+        replace(&link, "after").unwrap();
+
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "after");
+        assert_eq!(
+            std::fs::metadata(&target).unwrap().permissions().mode() & 0o777,
+            0o640
+        );
+    }
+
+    #[test]
+    fn adds_version_to_inline_table() {
+        let mut doc = doc("[dependencies]\ncore = { path = \"../core\" }\n");
+
+        let core = doc["dependencies"]["core"].as_table_like_mut().unwrap();
+        set_dependency_version(core, "=1.3.0");
+
+        assert_eq!(
+            doc.to_string(),
+            "[dependencies]\ncore = { path = \"../core\", version = \"=1.3.0\" }\n"
+        );
+    }
+
+    #[test]
+    fn render_keeps_crlf() {
+        let original = "[package]\r\nversion = \"1.0.0\"\r\n";
+        let mut doc = doc(original);
+
+        set_str(&mut doc["package"]["version"], "1.0.1");
+
+        assert_eq!(
+            render(&doc, original),
+            "[package]\r\nversion = \"1.0.1\"\r\n"
+        );
+        assert_eq!(render(&doc, ""), doc.to_string());
+    }
+
+    #[test]
+    fn set_str_keeps_comments() {
+        let mut doc = doc("[package]\nversion = \"1.0.0\" # keep me\n");
+
+        set_str(&mut doc["package"]["version"], "1.0.1");
+
+        assert_eq!(
+            doc.to_string(),
+            "[package]\nversion = \"1.0.1\" # keep me\n"
+        );
+    }
+}
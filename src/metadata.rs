@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use anyhow::{Result, anyhow, bail};
 use cargo_metadata::Package;
 
@@ -10,10 +12,26 @@ impl From<cargo_metadata::Metadata> for Metadata {
 }
 
 impl Metadata {
+    pub fn workspace_root(&self) -> &Path {
+        self.0.workspace_root.as_std_path()
+    }
+
     pub fn all_packages(&self) -> Result<Vec<&Package>> {
         Ok(self.0.packages.iter().collect())
     }
 
+    pub fn workspace_members(&self) -> Vec<&Package> {
+        self.0
+            .packages
+            .iter()
+            .filter(|p| self.0.workspace_members.contains(&p.id))
+            .collect()
+    }
+
+    pub fn workspace_metadata(&self) -> &serde_json::Value {
+        &self.0.workspace_metadata
+    }
+
     pub fn select_packages<'a>(
         &'a self,
         workspace: bool,
@@ -26,12 +44,7 @@ impl Metadata {
 
             (true, true) => {
                 // all workspace members
-                Ok(self
-                    .0
-                    .packages
-                    .iter()
-                    .filter(|p| self.0.workspace_members.contains(&p.id))
-                    .collect())
+                Ok(self.workspace_members())
             }
 
             (false, false) => {
@@ -55,12 +68,7 @@ impl Metadata {
                     Ok(vec![pkg])
                 } else {
                     // no root package → apply to all workspace members
-                    Ok(self
-                        .0
-                        .packages
-                        .iter()
-                        .filter(|p| self.0.workspace_members.contains(&p.id))
-                        .collect())
+                    Ok(self.workspace_members())
                 }
             }
         }
@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use cargo_metadata::MetadataCommand;
+use tempfile::TempDir;
+
+use crate::metadata::Metadata;
+
+/// A throwaway cargo workspace on disk, for tests that need real manifests
+/// and real `cargo metadata` output.
+pub struct Workspace {
+    dir: TempDir,
+}
+
+impl Workspace {
+    pub fn new(root_manifest: &str) -> Self {
+        let workspace = Self {
+            dir: tempfile::tempdir().unwrap(),
+        };
+        workspace.file("Cargo.toml", root_manifest);
+        workspace
+    }
+
+    /// A workspace listing `members`, which are added with [`Self::member`].
+    pub fn members(members: &[&str]) -> Self {
+        let members: Vec<String> = members.iter().map(|name| format!("\"{name}\"")).collect();
+        Self::new(&format!(
+            "[workspace]\nmembers = [{}]\nresolver = \"2\"\n",
+            members.join(", ")
+        ))
+    }
+
+    /// Members `core` at `core_version` and `app` 0.1.0, with `app_dependency`
+    /// as the `[dependencies]` entry of `app` on `core` (e.g.
+    /// `core = { path = "../core", version = "^1.2" }`).
+    pub fn two_members(core_version: &str, app_dependency: &str) -> Self {
+        let workspace = Self::members(&["core", "app"]);
+        workspace.member("core", core_version, "").member(
+            "app",
+            "0.1.0",
+            &format!("\n[dependencies]\n{app_dependency}\n"),
+        );
+        workspace
+    }
+
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn path(&self, relative: &str) -> PathBuf {
+        self.root().join(relative)
+    }
+
+    pub fn file(&self, relative: &str, contents: &str) -> &Self {
+        let path = self.path(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+        self
+    }
+
+    /// Add a library crate at `dir` with the given manifest.
+    pub fn package(&self, dir: &str, manifest: &str) -> &Self {
+        self.file(&format!("{dir}/Cargo.toml"), manifest);
+        self.file(&format!("{dir}/src/lib.rs"), "");
+        self
+    }
+
+    /// Add a library crate `name` at `version` in a directory of the same
+    /// name, with `rest` (e.g. dependency tables) ending its manifest.
+    pub fn member(&self, name: &str, version: &str, rest: &str) -> &Self {
+        self.package(
+            name,
+            &format!(
+                "[package]\nname = \"{name}\"\nversion = \"{version}\"\nedition = \"2021\"\n{rest}"
+            ),
+        )
+    }
+
+    pub fn metadata(&self) -> Metadata {
+        MetadataCommand::new()
+            .manifest_path(self.path("Cargo.toml"))
+            .no_deps()
+            .exec()
+            .unwrap()
+            .into()
+    }
+}
@@ -3,17 +3,10 @@ use std::collections::HashSet;
 use super::version::semantic_version::SemanticVersion;
 use anyhow::{Result, bail};
 
-pub fn select_single_version<T, I>(versions: I) -> Result<SemanticVersion>
-where
-    I: IntoIterator<Item = T>,
-    T: TryInto<SemanticVersion>,
-    anyhow::Error: From<T::Error>,
-{
-    let mut set = HashSet::new();
-
-    for v in versions {
-        set.insert(v.try_into().map_err(anyhow::Error::from)?);
-    }
+pub fn select_single_version(
+    versions: impl IntoIterator<Item = SemanticVersion>,
+) -> Result<SemanticVersion> {
+    let set: HashSet<_> = versions.into_iter().collect();
 
     match set.len() {
         0 => bail!("No packages found."),
@@ -29,7 +22,11 @@ mod tests {
     use super::*;
 
     fn sv(s: &str) -> SemanticVersion {
-        SemanticVersion::try_from(cargo_metadata::semver::Version::parse(s).unwrap()).unwrap()
+        SemanticVersion::parse_with(
+            cargo_metadata::semver::Version::parse(s).unwrap(),
+            crate::version::scheme::STRICT.clone(),
+        )
+        .unwrap()
     }
 
     #[test]
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use cargo_metadata::semver::Version;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::{
+    scheme::PrereleaseScheme,
+    semantic_version::{Prerelease, SemanticVersion},
+};
+
+/// An identifier, optionally followed by a counter (`beta`, `rc1`, `beta-2`).
+static LEGACY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?<ident>[A-Za-z][0-9A-Za-z]*?)[-.]?(?<counter>[0-9]+)?$").expect("valid regex")
+});
+
+/// Propose a version that `scheme` accepts for a version whose prerelease it
+/// rejects, e.g. `1.2.3-beta` → `1.2.3-beta.1` or `1.2.3-rc1` → `1.2.3-rc.1`.
+pub fn normalize(version: &Version, scheme: Arc<PrereleaseScheme>) -> Result<SemanticVersion> {
+    let pre = version.pre.as_str();
+
+    let captures = LEGACY
+        .captures(pre)
+        .ok_or_else(|| anyhow!("cannot infer an identifier and counter from `{}`", pre))?;
+
+    let iteration = match captures.name("counter") {
+        Some(counter) => counter
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_| anyhow!("counter in `{}` is too large", pre))?,
+        None => 1,
+    };
+
+    let prerelease = Prerelease {
+        ident: captures["ident"].to_string(),
+        iteration,
+    };
+
+    let mut release = version.clone();
+    release.pre = cargo_metadata::semver::Prerelease::EMPTY;
+
+    SemanticVersion::parse_with(release, scheme)?.with_prerelease(prerelease)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::scheme::STRICT;
+
+    fn normalized(s: &str) -> Result<String> {
+        normalize(&Version::parse(s).unwrap(), STRICT.clone()).map(|v| v.to_string())
+    }
+
+    #[test]
+    fn missing_counter_starts_at_one() {
+        assert_eq!(normalized("1.2.3-beta").unwrap(), "1.2.3-beta.1");
+    }
+
+    #[test]
+    fn attached_counter_is_split() {
+        assert_eq!(normalized("1.2.3-rc1").unwrap(), "1.2.3-rc.1");
+    }
+
+    #[test]
+    fn dashed_counter_is_split() {
+        assert_eq!(normalized("1.2.3-beta-2").unwrap(), "1.2.3-beta.2");
+    }
+
+    #[test]
+    fn build_metadata_is_kept() {
+        assert_eq!(
+            normalized("1.2.3-alpha+git.abc").unwrap(),
+            "1.2.3-alpha.1+git.abc"
+        );
+    }
+
+    #[test]
+    fn extra_components_are_unsupported() {
+        assert!(normalized("1.2.3-rc.1.hotfix").is_err());
+    }
+
+    #[test]
+    fn numeric_prerelease_is_unsupported() {
+        assert!(normalized("1.2.3-1").is_err());
+    }
+
+    #[test]
+    fn identifier_must_be_allowed() {
+        let scheme = PrereleaseScheme::default()
+            .with_identifiers(vec!["alpha".into(), "beta".into()])
+            .unwrap();
+
+        let result = normalize(&Version::parse("1.2.3-rc1").unwrap(), Arc::new(scheme));
+
+        assert!(result.is_err());
+    }
+}
@@ -1,2 +1,5 @@
+pub mod legacy;
+pub mod requirement;
+pub mod scheme;
 pub mod semantic_version;
 pub mod transition;
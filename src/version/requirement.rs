@@ -0,0 +1,111 @@
+use cargo_metadata::semver::{Op, Version, VersionReq};
+
+/// Point a single-comparator requirement at `version`, keeping its operator
+/// and precision (`^1.2` → `^1.3`, `=1.2.0` → `=1.3.0`, `1.2.*` → `1.3.*`).
+///
+/// Returns `None` for requirements that cannot be moved without changing
+/// their meaning, such as ranges (`>=1, <2`) or upper bounds (`<2`). The
+/// result may read the same as `requirement` (`^1` for `1.3.0`).
+pub fn retarget(requirement: &str, version: &Version) -> Option<String> {
+    let parsed = VersionReq::parse(requirement).ok()?;
+    let [comparator] = parsed.comparators.as_slice() else {
+        return None;
+    };
+
+    let Version {
+        major,
+        minor,
+        patch,
+        ..
+    } = version;
+
+    let operator = match comparator.op {
+        Op::Exact => "=",
+        Op::GreaterEq => ">=",
+        Op::Tilde => "~",
+        Op::Caret if requirement.trim_start().starts_with('^') => "^",
+        Op::Caret => "",
+        // wildcards never match prereleases
+        Op::Wildcard if !version.pre.is_empty() => return None,
+        Op::Wildcard => {
+            return Some(match comparator.minor {
+                Some(_) => format!("{major}.{minor}.*"),
+                None => format!("{major}.*"),
+            });
+        }
+        _ => return None,
+    };
+
+    // a prerelease can only be written in full
+    let body = match (comparator.minor, comparator.patch) {
+        _ if !version.pre.is_empty() => format!("{major}.{minor}.{patch}-{}", version.pre),
+        (Some(_), Some(_)) => format!("{major}.{minor}.{patch}"),
+        (Some(_), None) => format!("{major}.{minor}"),
+        (None, _) => format!("{major}"),
+    };
+
+    Some(format!("{operator}{body}"))
+}
+
+/// Whether `version` satisfies `requirement`. Unparseable requirements are
+/// never satisfied.
+pub fn satisfied(requirement: &str, version: &Version) -> bool {
+    VersionReq::parse(requirement).is_ok_and(|requirement| requirement.matches(version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retarget(requirement: &str, version: &str) -> Option<String> {
+        super::retarget(requirement, &Version::parse(version).unwrap())
+    }
+
+    #[test]
+    fn keeps_operator() {
+        assert_eq!(retarget("=1.2.0", "1.3.0").as_deref(), Some("=1.3.0"));
+        assert_eq!(retarget("^1.2.0", "1.3.0").as_deref(), Some("^1.3.0"));
+        assert_eq!(retarget(">=1.2.0", "1.3.0").as_deref(), Some(">=1.3.0"));
+        assert_eq!(retarget("1.2.0", "1.3.0").as_deref(), Some("1.3.0"));
+    }
+
+    #[test]
+    fn keeps_precision() {
+        assert_eq!(retarget("^1.2", "1.3.0").as_deref(), Some("^1.3"));
+        assert_eq!(retarget("~1.2", "1.3.4").as_deref(), Some("~1.3"));
+        assert_eq!(retarget("1", "2.0.0").as_deref(), Some("2"));
+        assert_eq!(retarget("^1", "1.3.0").as_deref(), Some("^1"));
+    }
+
+    #[test]
+    fn keeps_wildcards() {
+        assert_eq!(retarget("1.2.*", "1.3.0").as_deref(), Some("1.3.*"));
+        assert_eq!(retarget("1.*", "2.0.0").as_deref(), Some("2.*"));
+    }
+
+    #[test]
+    fn prerelease_uses_full_precision() {
+        assert_eq!(
+            retarget("^1.2", "1.3.0-rc.1").as_deref(),
+            Some("^1.3.0-rc.1")
+        );
+        assert_eq!(retarget("1.2.*", "1.3.0-rc.1"), None);
+    }
+
+    #[test]
+    fn satisfaction() {
+        let v = |s| Version::parse(s).unwrap();
+
+        assert!(satisfied("^1.2", &v("1.2.5")));
+        assert!(!satisfied("^1.2", &v("2.0.0")));
+        assert!(!satisfied("^1.2", &v("1.3.0-rc.1")));
+        assert!(!satisfied("not a requirement", &v("1.0.0")));
+    }
+
+    #[test]
+    fn leaves_ranges_alone() {
+        assert_eq!(retarget(">=1.2, <2", "2.0.0"), None);
+        assert_eq!(retarget("<2", "2.0.0"), None);
+        assert_eq!(retarget("*", "2.0.0"), None);
+    }
+}
@@ -0,0 +1,396 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow, bail};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::semantic_version::Prerelease;
+
+/// The scheme used when no configuration is involved.
+pub static STRICT: Lazy<Arc<PrereleaseScheme>> =
+    Lazy::new(|| Arc::new(PrereleaseScheme::default()));
+
+/// How a prerelease is spelled in a version string.
+#[derive(Debug, Clone, Default)]
+pub enum PrereleaseFormat {
+    /// `<identifier>.<counter>`, e.g. `beta.2`.
+    #[default]
+    Strict,
+    /// A regex with `ident` and `counter` named captures, and a template with
+    /// `{ident}` and `{counter}` placeholders to render it back.
+    Custom { pattern: Regex, template: String },
+}
+
+impl PartialEq for PrereleaseFormat {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Strict, Self::Strict) => true,
+            (
+                Self::Custom { pattern, template },
+                Self::Custom {
+                    pattern: other_pattern,
+                    template: other_template,
+                },
+            ) => pattern.as_str() == other_pattern.as_str() && template == other_template,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for PrereleaseFormat {}
+
+impl PrereleaseFormat {
+    pub fn custom(pattern: &str, template: &str) -> Result<Self> {
+        // anchor the pattern so it has to describe the whole prerelease
+        let pattern = Regex::new(&format!("^(?:{pattern})$"))
+            .with_context(|| format!("Invalid prerelease pattern `{}`.", pattern))?;
+
+        for group in ["ident", "counter"] {
+            if !pattern.capture_names().any(|name| name == Some(group)) {
+                bail!(
+                    "Prerelease pattern must have a `(?<{}>...)` capture group.",
+                    group
+                );
+            }
+
+            if !template.contains(&format!("{{{group}}}")) {
+                bail!("Prerelease template must contain `{{{}}}`.", group);
+            }
+        }
+
+        // every match has to capture both groups, or parsing has nothing to read
+        if pattern.static_captures_len().is_none() {
+            bail!("Prerelease pattern capture groups must not be optional.");
+        }
+
+        Ok(Self::Custom {
+            pattern,
+            template: template.to_string(),
+        })
+    }
+
+    pub fn parse(&self, s: &str) -> Result<Prerelease> {
+        let Self::Custom { pattern, .. } = self else {
+            return Prerelease::parse(s);
+        };
+
+        let captures = pattern.captures(s).ok_or_else(|| {
+            anyhow!(
+                "Invalid prerelease `{}`: does not match the configured pattern",
+                s
+            )
+        })?;
+
+        let group = |name| {
+            captures
+                .name(name)
+                .map(|m| m.as_str())
+                .ok_or_else(|| anyhow!("Invalid prerelease `{}`: missing {}", s, name))
+        };
+
+        let iteration = group("counter")?
+            .parse::<u64>()
+            .map_err(|_| anyhow!("Invalid prerelease `{}`: counter must be numeric", s))?;
+
+        Ok(Prerelease {
+            ident: group("ident")?.to_string(),
+            iteration,
+        })
+    }
+
+    pub fn render(&self, prerelease: &Prerelease) -> Result<cargo_metadata::semver::Prerelease> {
+        let Self::Custom { template, .. } = self else {
+            return Ok(prerelease.to_semver());
+        };
+
+        let rendered = template
+            .replace("{ident}", &prerelease.ident)
+            .replace("{counter}", &prerelease.iteration.to_string());
+
+        // what we write has to read back as the same prerelease
+        match self.parse(&rendered) {
+            Ok(parsed)
+                if parsed.ident == prerelease.ident && parsed.iteration == prerelease.iteration => {
+            }
+            _ => bail!(
+                "Prerelease `{}` does not match the configured prerelease pattern.",
+                rendered
+            ),
+        }
+
+        cargo_metadata::semver::Prerelease::new(&rendered)
+            .with_context(|| format!("`{}` is not a valid SemVer prerelease", rendered))
+    }
+}
+
+/// Rules that prereleases must follow: how they are spelled and which
+/// identifiers are allowed.
+///
+/// Without an identifier list any identifier is accepted and prereleases are
+/// ordered lexically, as SemVer does.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrereleaseScheme {
+    format: PrereleaseFormat,
+    identifiers: Option<Vec<String>>,
+}
+
+impl PrereleaseScheme {
+    pub fn with_format(mut self, format: PrereleaseFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Only allow `identifiers`, ordered from earliest to latest
+    /// (e.g. `["dev", "alpha", "beta", "rc"]`).
+    pub fn with_identifiers(mut self, identifiers: Vec<String>) -> Result<Self> {
+        if identifiers.is_empty() {
+            bail!("Prerelease identifier list must not be empty.");
+        }
+
+        for (i, ident) in identifiers.iter().enumerate() {
+            if identifiers[..i].contains(ident) {
+                bail!(
+                    "Prerelease identifier `{}` is listed more than once.",
+                    ident
+                );
+            }
+
+            // must survive the round trip through the prerelease format
+            let sample = Prerelease {
+                ident: ident.clone(),
+                iteration: 1,
+            };
+            let parsed = self
+                .render(&sample)
+                .and_then(|rendered| self.parse(rendered.as_str()));
+
+            if !matches!(parsed, Ok(p) if p.ident == *ident) {
+                bail!("Invalid prerelease identifier `{}`.", ident);
+            }
+        }
+
+        self.identifiers = Some(identifiers);
+        Ok(self)
+    }
+
+    pub fn format(&self) -> &PrereleaseFormat {
+        &self.format
+    }
+
+    pub fn identifiers(&self) -> Option<&[String]> {
+        self.identifiers.as_deref()
+    }
+
+    pub fn parse(&self, s: &str) -> Result<Prerelease> {
+        Prerelease::parse_with(s, &self.format)
+    }
+
+    pub fn render(&self, prerelease: &Prerelease) -> Result<cargo_metadata::semver::Prerelease> {
+        prerelease.to_semver_with(&self.format)
+    }
+
+    /// Reject identifiers outside the configured list.
+    pub fn check(&self, ident: &str) -> Result<()> {
+        match &self.identifiers {
+            Some(allowed) if !allowed.iter().any(|a| a == ident) => bail!(
+                "Prerelease identifier `{}` is not allowed (expected one of: {}).",
+                ident,
+                allowed.join(", ")
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether moving from `from` to `to` goes forward in the release cycle.
+    pub fn is_forward(&self, from: &Prerelease, to: &Prerelease) -> bool {
+        match &self.identifiers {
+            Some(allowed) => {
+                let rank = |p: &Prerelease| allowed.iter().position(|a| *a == p.ident);
+                match (rank(from), rank(to)) {
+                    (Some(from), Some(to)) => to > from,
+                    _ => false,
+                }
+            }
+            // as SemVer orders `<identifier>.<counter>`, whatever the format
+            None => (&to.ident, to.iteration) > (&from.ident, from.iteration),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheme(identifiers: &[&str]) -> PrereleaseScheme {
+        PrereleaseScheme::default()
+            .with_identifiers(identifiers.iter().map(|s| s.to_string()).collect())
+            .unwrap()
+    }
+
+    fn pre(s: &str) -> Prerelease {
+        Prerelease::parse(s).unwrap()
+    }
+
+    #[test]
+    fn unconfigured_accepts_anything() {
+        assert!(PrereleaseScheme::default().check("preview").is_ok());
+    }
+
+    #[test]
+    fn unconfigured_orders_lexically() {
+        let scheme = PrereleaseScheme::default();
+
+        assert!(scheme.is_forward(&pre("alpha.3"), &pre("beta.1")));
+        assert!(!scheme.is_forward(&pre("rc.1"), &pre("preview.1")));
+    }
+
+    #[test]
+    fn check_rejects_unlisted() {
+        let scheme = scheme(&["alpha", "beta", "rc"]);
+
+        assert!(scheme.check("beta").is_ok());
+        assert!(scheme.check("preview").is_err());
+    }
+
+    #[test]
+    fn configured_order_wins_over_lexical() {
+        let scheme = scheme(&["dev", "alpha", "beta", "rc", "preview"]);
+
+        assert!(scheme.is_forward(&pre("dev.4"), &pre("alpha.1")));
+        assert!(scheme.is_forward(&pre("rc.2"), &pre("preview.1")));
+        assert!(!scheme.is_forward(&pre("beta.1"), &pre("alpha.1")));
+        assert!(!scheme.is_forward(&pre("beta.1"), &pre("beta.1")));
+    }
+
+    fn custom(pattern: &str, template: &str) -> PrereleaseFormat {
+        PrereleaseFormat::custom(pattern, template).unwrap()
+    }
+
+    #[test]
+    fn custom_format_without_separator() {
+        let format = custom(r"(?<ident>[a-z]+)(?<counter>\d+)", "{ident}{counter}");
+
+        let parsed = format.parse("alpha1").unwrap();
+
+        assert_eq!(parsed.ident, "alpha");
+        assert_eq!(parsed.iteration, 1);
+        assert_eq!(
+            format.render(&parsed.increment()).unwrap().as_str(),
+            "alpha2"
+        );
+    }
+
+    #[test]
+    fn custom_format_with_dash() {
+        let format = custom(r"(?<ident>[a-z]+)-(?<counter>\d+)", "{ident}-{counter}");
+
+        let parsed = format.parse("beta-2").unwrap();
+
+        assert_eq!(parsed.ident, "beta");
+        assert_eq!(
+            format.render(&parsed.increment()).unwrap().as_str(),
+            "beta-3"
+        );
+    }
+
+    #[test]
+    fn custom_format_with_suffix() {
+        let format = custom(
+            r"(?<ident>[a-z]+)\.(?<counter>\d+)\.hotfix",
+            "{ident}.{counter}.hotfix",
+        );
+
+        let parsed = format.parse("rc.1.hotfix").unwrap();
+
+        assert_eq!(parsed.ident, "rc");
+        assert_eq!(parsed.iteration, 1);
+        assert_eq!(format.render(&parsed).unwrap().as_str(), "rc.1.hotfix");
+    }
+
+    #[test]
+    fn custom_format_is_anchored() {
+        let format = custom(r"(?<ident>[a-z]+)(?<counter>\d+)", "{ident}{counter}");
+
+        assert!(format.parse("xalpha1.extra").is_err());
+    }
+
+    #[test]
+    fn custom_format_requires_named_groups() {
+        assert!(PrereleaseFormat::custom(r"([a-z]+)(\d+)", "{ident}{counter}").is_err());
+        assert!(PrereleaseFormat::custom(r"(?<ident>[a-z]+)(?<counter>\d+)", "{ident}").is_err());
+    }
+
+    #[test]
+    fn custom_format_rejects_optional_groups() {
+        let err = PrereleaseFormat::custom(
+            r"(?<ident>[a-z]+)(?:-(?<counter>\d+))?",
+            "{ident}-{counter}",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("must not be optional"));
+
+        assert!(
+            PrereleaseFormat::custom(
+                r"(?<ident>[a-z]+)(?<counter>\d+)|(?<counter2>\d+)",
+                "{ident}{counter}"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn custom_format_rejects_unparseable_render() {
+        let format = custom(r"(?<ident>[a-z]+)(?<counter>\d+)", "{ident}{counter}");
+
+        let result = format.render(&Prerelease {
+            ident: "RC".into(),
+            iteration: 1,
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn identifiers_checked_against_format() {
+        let scheme = PrereleaseScheme::default().with_format(custom(
+            r"(?<ident>[a-z]+)(?<counter>\d+)",
+            "{ident}{counter}",
+        ));
+
+        assert!(
+            scheme
+                .clone()
+                .with_identifiers(vec!["alpha".into()])
+                .is_ok()
+        );
+        assert!(scheme.with_identifiers(vec!["Alpha".into()]).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_list() {
+        assert!(
+            PrereleaseScheme::default()
+                .with_identifiers(vec![])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_duplicates() {
+        let result = PrereleaseScheme::default().with_identifiers(vec![
+            "alpha".into(),
+            "beta".into(),
+            "alpha".into(),
+        ]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_identifier() {
+        let result =
+            PrereleaseScheme::default().with_identifiers(vec!["alpha.beta".into(), "1".into()]);
+
+        assert!(result.is_err());
+    }
+}
@@ -1,8 +1,41 @@
+use std::sync::Arc;
+
 use anyhow::{Context, Result, bail};
 use cargo_metadata::semver::Version;
 
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct SemanticVersion(Version);
+use super::scheme::{PrereleaseFormat, PrereleaseScheme};
+
+/// A SemVer version whose prerelease follows a [`PrereleaseScheme`].
+///
+/// Equality, ordering and hashing only consider the version itself.
+#[derive(Debug, Clone)]
+pub struct SemanticVersion(Version, Arc<PrereleaseScheme>);
+
+impl PartialEq for SemanticVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for SemanticVersion {}
+
+impl PartialOrd for SemanticVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemanticVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl std::hash::Hash for SemanticVersion {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
 
 impl std::fmt::Display for SemanticVersion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -11,6 +44,24 @@ impl std::fmt::Display for SemanticVersion {
 }
 
 impl SemanticVersion {
+    /// Validate `version` against `scheme`, which then governs every transition.
+    pub fn parse_with(version: Version, scheme: Arc<PrereleaseScheme>) -> Result<Self> {
+        if !version.pre.is_empty() {
+            let prerelease = scheme.parse(version.pre.as_str())?;
+            scheme.check(&prerelease.ident)?;
+        }
+
+        Ok(Self(version, scheme))
+    }
+
+    pub fn scheme(&self) -> &PrereleaseScheme {
+        &self.1
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.0
+    }
+
     pub fn major(&self) -> u64 {
         self.0.major
     }
@@ -28,7 +79,7 @@ impl SemanticVersion {
             return Ok(None);
         }
 
-        match Prerelease::parse(self.0.pre.as_str()) {
+        match self.1.parse(self.0.pre.as_str()) {
             Ok(p) => Ok(Some(p)),
             Err(e) => {
                 bail!("Prerelease should have been validated. More details: {}", e);
@@ -63,7 +114,8 @@ impl SemanticVersion {
     }
 
     pub fn with_prerelease(mut self, prerelease: Prerelease) -> Result<Self> {
-        self.0.pre = prerelease.to_semver();
+        self.1.check(&prerelease.ident)?;
+        self.0.pre = self.1.render(&prerelease)?;
 
         Ok(self)
     }
@@ -83,18 +135,6 @@ impl SemanticVersion {
     }
 }
 
-impl TryFrom<Version> for SemanticVersion {
-    type Error = anyhow::Error;
-
-    fn try_from(val: Version) -> Result<Self> {
-        if !val.pre.is_empty() {
-            let _ = Prerelease::parse(val.pre.as_str())?;
-        }
-
-        Ok(Self(val))
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct Prerelease {
     pub ident: String,
@@ -102,7 +142,14 @@ pub struct Prerelease {
 }
 
 impl Prerelease {
-    pub fn parse(s: &str) -> anyhow::Result<Self> {
+    /// Parse `s` using the given format.
+    pub fn parse_with(s: &str, format: &PrereleaseFormat) -> anyhow::Result<Self> {
+        format.parse(s)
+    }
+
+    /// Parse the strict `<identifier>.<counter>` format, for
+    /// [`PrereleaseFormat::Strict`]. Everything else goes through the scheme.
+    pub(super) fn parse(s: &str) -> anyhow::Result<Self> {
         let mut parts = s.split('.');
 
         let ident = parts
@@ -130,13 +177,22 @@ impl Prerelease {
         }
     }
 
-    pub fn to_semver(&self) -> cargo_metadata::semver::Prerelease {
+    /// Render in the strict `<identifier>.<counter>` format, for
+    /// [`PrereleaseFormat::Strict`]. Everything else goes through the scheme.
+    pub(super) fn to_semver(&self) -> cargo_metadata::semver::Prerelease {
         cargo_metadata::semver::Prerelease::new(&format!("{}.{}", self.ident, self.iteration))
             .expect("always valid")
     }
+
+    pub fn to_semver_with(
+        &self,
+        format: &PrereleaseFormat,
+    ) -> anyhow::Result<cargo_metadata::semver::Prerelease> {
+        format.render(self)
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ReleaseLevel {
     Patch,
     Minor,
@@ -146,10 +202,12 @@ pub enum ReleaseLevel {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::version::scheme::STRICT;
 
     #[test]
     fn test_build_empty() {
-        let v: SemanticVersion = Version::parse("1.2.3").unwrap().try_into().unwrap();
+        let v =
+            SemanticVersion::parse_with(Version::parse("1.2.3").unwrap(), STRICT.clone()).unwrap();
         let build = v.build();
 
         assert!(build.is_none());
@@ -157,7 +215,8 @@ mod tests {
 
     #[test]
     fn test_build_not_empty() {
-        let v: SemanticVersion = Version::parse("1.2.3+asdf").unwrap().try_into().unwrap();
+        let v = SemanticVersion::parse_with(Version::parse("1.2.3+asdf").unwrap(), STRICT.clone())
+            .unwrap();
         let build = v.build();
 
         assert_eq!(build, Some("asdf".to_string()))
@@ -166,7 +225,7 @@ mod tests {
     #[test]
     fn test_semantic_version_display() {
         let v = Version::parse("1.2.3").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         assert_eq!(sv.to_string(), "1.2.3");
     }
@@ -174,7 +233,7 @@ mod tests {
     #[test]
     fn test_semantic_version_is_prerelease() {
         let v = Version::parse("1.2.3-beta.1").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         assert!(sv.is_prerelease());
     }
@@ -182,7 +241,7 @@ mod tests {
     #[test]
     fn test_semantic_version_not_prerelease() {
         let v = Version::parse("1.2.3").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         assert!(!sv.is_prerelease());
     }
@@ -190,7 +249,7 @@ mod tests {
     #[test]
     fn test_semantic_version_clear_prerelease() {
         let v = Version::parse("1.2.3-beta.1").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         let cleared = sv.clear_prerelease().unwrap();
 
@@ -201,7 +260,7 @@ mod tests {
     #[test]
     fn test_semantic_version_with_build() {
         let v = Version::parse("1.2.3").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         let with_meta = sv.with_build(Some("build.42".to_string())).unwrap();
 
@@ -211,7 +270,7 @@ mod tests {
     #[test]
     fn test_semantic_version_with_invalid_metadata_fails() {
         let v = Version::parse("1.2.3").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         let result = sv.with_build(Some("invalid metadata".to_string()));
 
@@ -221,7 +280,7 @@ mod tests {
     #[test]
     fn test_semantic_version_with_prerelease() {
         let v = Version::parse("1.2.3").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         let pr = Prerelease::parse("alpha.7").unwrap();
         let with_pr = sv.with_prerelease(pr).unwrap();
@@ -232,7 +291,7 @@ mod tests {
     #[test]
     fn test_bump_patch() {
         let v = Version::parse("1.2.3").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         let bumped = sv.bump_level(ReleaseLevel::Patch).unwrap();
 
@@ -242,7 +301,7 @@ mod tests {
     #[test]
     fn test_bump_minor() {
         let v = Version::parse("1.2.3").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         let bumped = sv.bump_level(ReleaseLevel::Minor).unwrap();
 
@@ -252,7 +311,7 @@ mod tests {
     #[test]
     fn test_bump_major() {
         let v = Version::parse("1.2.3").unwrap();
-        let sv = SemanticVersion::try_from(v).unwrap();
+        let sv = SemanticVersion::parse_with(v, STRICT.clone()).unwrap();
 
         let bumped = sv.bump_level(ReleaseLevel::Major).unwrap();
 
@@ -264,11 +323,52 @@ mod tests {
         // semver allows this syntactically, but your wrapper explicitly rejects it
         let v = Version::parse("1.2.3-beta").unwrap();
 
-        let result = SemanticVersion::try_from(v);
+        let result = SemanticVersion::parse_with(v, STRICT.clone());
 
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_with_rejects_unlisted_identifier() {
+        let scheme = PrereleaseScheme::default()
+            .with_identifiers(vec!["alpha".into(), "beta".into()])
+            .unwrap();
+        let v = Version::parse("1.2.3-rc.1").unwrap();
+
+        let result = SemanticVersion::parse_with(v, Arc::new(scheme));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_with_prerelease_rejects_unlisted_identifier() {
+        let scheme = PrereleaseScheme::default()
+            .with_identifiers(vec!["alpha".into(), "beta".into()])
+            .unwrap();
+        let v = Version::parse("1.2.3").unwrap();
+        let sv = SemanticVersion::parse_with(v, Arc::new(scheme)).unwrap();
+
+        let result = sv.with_prerelease(Prerelease::parse("rc.1").unwrap());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_with_custom_format() {
+        let format =
+            PrereleaseFormat::custom(r"(?<ident>[a-z]+)(?<counter>\d+)", "{ident}{counter}")
+                .unwrap();
+        let scheme = Arc::new(PrereleaseScheme::default().with_format(format));
+        let v = Version::parse("1.2.3-rc1").unwrap();
+
+        let sv = SemanticVersion::parse_with(v, scheme).unwrap();
+        let pr = sv.prerelease().unwrap().unwrap();
+        let next = sv.clone().with_prerelease(pr.increment()).unwrap();
+
+        assert_eq!(pr.ident, "rc");
+        assert_eq!(next.to_string(), "1.2.3-rc2");
+    }
+
     #[test]
     fn test_prerelease_parse_rejects_extra_components() {
         let result = Prerelease::parse("beta.1.extra");
@@ -34,6 +34,8 @@ impl SemanticVersion {
                 Prerelease : TransitionPrerelease;
                 Prerelease : FinalizeRelease;
                 Release : BumpRelease;
+                Release : Set;
+                Prerelease : Set;
             }
             @illegal {
                 Prerelease : StartPrerelease
@@ -1,12 +1,15 @@
+use std::sync::Arc;
+
 use crate::version::semantic_version::{Prerelease, SemanticVersion};
 
 use anyhow::{Result, bail};
+use cargo_metadata::semver::Version;
 
 mod fsm;
 
 use super::semantic_version::ReleaseLevel;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TransitionInput {
     StartPrerelease {
         level: ReleaseLevel,
@@ -27,6 +30,10 @@ pub enum TransitionInput {
         level: ReleaseLevel,
         metadata: Option<String>,
     },
+    Set {
+        version: Version,
+        allow_downgrade: bool,
+    },
 }
 
 impl SemanticVersion {
@@ -45,6 +52,10 @@ impl SemanticVersion {
             }
             TransitionInput::FinalizeRelease { metadata } => self.finalize_release(metadata),
             TransitionInput::BumpRelease { level, metadata } => self.bump_release(level, metadata),
+            TransitionInput::Set {
+                version,
+                allow_downgrade,
+            } => self.set(version, allow_downgrade),
         }
     }
 
@@ -84,7 +95,8 @@ impl SemanticVersion {
 
         let old_prerelease = self.prerelease()?.expect("illegal state");
 
-        if new_prerelease.to_semver() <= old_prerelease.to_semver() {
+        self.scheme().check(&new_prerelease.ident)?;
+        if !self.scheme().is_forward(&old_prerelease, &new_prerelease) {
             bail!("New prerelease must be further than old prerelease.")
         }
 
@@ -100,16 +112,70 @@ impl SemanticVersion {
     fn bump_release(&self, level: ReleaseLevel, metadata: Option<String>) -> Result<Self> {
         self.clone().bump_level(level)?.with_build(metadata)
     }
+
+    fn set(&self, version: Version, allow_downgrade: bool) -> Result<Self> {
+        let target = Self::parse_with(version, Arc::new(self.scheme().clone()))?;
+
+        if !allow_downgrade && target.precedes(self)? {
+            bail!(
+                "Refusing to go back from {} to {} (use --allow-downgrade to force it).",
+                self,
+                target
+            );
+        }
+
+        Ok(target)
+    }
+
+    /// Whether `self` comes before `other`, ordering prereleases of the same
+    /// release by the scheme rather than lexically.
+    pub fn precedes(&self, other: &Self) -> Result<bool> {
+        let release = |v: &Self| (v.major(), v.minor(), v.patch());
+
+        if release(self) != release(other) {
+            return Ok(release(self) < release(other));
+        }
+
+        Ok(match (self.prerelease()?, other.prerelease()?) {
+            (Some(pre), Some(other_pre)) if pre.ident == other_pre.ident => {
+                pre.iteration < other_pre.iteration
+            }
+            (Some(pre), Some(other_pre)) => self.scheme().is_forward(&pre, &other_pre),
+            // a prerelease comes before its release
+            (Some(_), None) => true,
+            (None, _) => false,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::sync::Arc;
+
     use cargo_metadata::semver::Version;
 
+    use crate::version::scheme::PrereleaseScheme;
+
     fn sv(s: &str) -> SemanticVersion {
-        SemanticVersion::try_from(Version::parse(s).unwrap()).unwrap()
+        SemanticVersion::parse_with(
+            Version::parse(s).unwrap(),
+            crate::version::scheme::STRICT.clone(),
+        )
+        .unwrap()
+    }
+
+    fn ordered(s: &str) -> SemanticVersion {
+        let scheme = PrereleaseScheme::default()
+            .with_identifiers(vec![
+                "dev".into(),
+                "alpha".into(),
+                "beta".into(),
+                "rc".into(),
+            ])
+            .unwrap();
+        SemanticVersion::parse_with(Version::parse(s).unwrap(), Arc::new(scheme)).unwrap()
     }
 
     #[test]
@@ -199,6 +265,57 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn transition_prerelease_follows_configured_order() {
+        let v = ordered("1.2.3-dev.2");
+
+        let next = v
+            .apply(TransitionInput::TransitionPrerelease {
+                pre: "alpha".into(),
+                metadata: None,
+            })
+            .unwrap();
+
+        assert_eq!(next.to_string(), "1.2.3-alpha.1");
+    }
+
+    #[test]
+    fn transition_prerelease_rejects_backwards_in_configured_order() {
+        let v = ordered("1.2.3-rc.1");
+
+        let result = v.apply(TransitionInput::TransitionPrerelease {
+            pre: "beta".into(),
+            metadata: None,
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn transition_prerelease_rejects_unlisted_identifier() {
+        let v = ordered("1.2.3-rc.1");
+
+        let result = v.apply(TransitionInput::TransitionPrerelease {
+            pre: "preview".into(),
+            metadata: None,
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn start_prerelease_rejects_unlisted_identifier() {
+        let v = ordered("1.2.3");
+
+        let result = v.apply(TransitionInput::StartPrerelease {
+            level: ReleaseLevel::Minor,
+            pre: "preview".into(),
+            metadata: None,
+        });
+
+        assert!(result.is_err());
+    }
+
     #[test]
     fn transition_prerelease_fails_on_release() {
         let v = sv("1.2.3");
@@ -283,4 +400,60 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    fn set(version: &str, allow_downgrade: bool) -> TransitionInput {
+        TransitionInput::Set {
+            version: Version::parse(version).unwrap(),
+            allow_downgrade,
+        }
+    }
+
+    #[test]
+    fn set_moves_forward_from_any_state() {
+        assert_eq!(
+            sv("1.2.3")
+                .apply(set("2.0.0-rc.1", false))
+                .unwrap()
+                .to_string(),
+            "2.0.0-rc.1"
+        );
+        assert_eq!(
+            sv("1.2.3-beta.2")
+                .apply(set("1.5.0", false))
+                .unwrap()
+                .to_string(),
+            "1.5.0"
+        );
+    }
+
+    #[test]
+    fn set_refuses_downgrade_unless_allowed() {
+        let v = sv("1.2.3");
+
+        assert!(v.apply(set("1.2.3-rc.1", false)).is_err());
+        assert_eq!(v.apply(set("1.0.0", true)).unwrap().to_string(), "1.0.0");
+    }
+
+    #[test]
+    fn set_follows_prerelease_scheme() {
+        let v = ordered("1.2.3");
+
+        assert!(v.apply(set("2.0.0-preview.1", false)).is_err());
+        assert!(v.apply(set("2.0.0-rc", false)).is_err());
+        assert!(v.apply(set("2.0.0-alpha.1", false)).is_ok());
+    }
+
+    #[test]
+    fn set_orders_prereleases_by_scheme() {
+        let dev = ordered("1.0.0-dev.1");
+        let alpha = ordered("1.0.0-alpha.1");
+
+        assert_eq!(
+            dev.apply(set("1.0.0-alpha.1", false)).unwrap().to_string(),
+            "1.0.0-alpha.1"
+        );
+        assert!(alpha.apply(set("1.0.0-dev.1", false)).is_err());
+        assert!(alpha.apply(set("1.0.0-dev.1", true)).is_ok());
+        assert!(alpha.apply(set("1.0.0-alpha.2", false)).is_ok());
+    }
 }